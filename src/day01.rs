@@ -0,0 +1,608 @@
+//! Day 1: Sonar Sweep.
+
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::io::BufRead;
+
+use crate::grid::Grid;
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+use crate::RenderOptions;
+use crate::Rendering;
+
+pub struct Day1;
+
+impl Day for Day1 {
+    const DAY: u32 = 1;
+    const TITLE: &'static str = "Sonar Sweep";
+    type Input = String;
+
+    /// Checks every depth without keeping any, so bad input is reported
+    /// here; the parts parse the depths again as they stream through them.
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        if let Some(err) = parse_depths(input).find_map(Result::err) {
+            return Err(err.into());
+        }
+        Ok(String::from(input))
+    }
+
+    fn part1(input: Self::Input) -> Result<Answer, PuzzleError> {
+        count_increases(input.as_str(), 1)
+    }
+
+    fn part2(input: Self::Input) -> Result<Answer, PuzzleError> {
+        count_increases(input.as_str(), 3)
+    }
+
+    /// Both parts draw the same report on the depths, as wide as the
+    /// terminal.
+    fn render(
+        _part: u32,
+        input: &Self::Input,
+        options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError> {
+        let mut report = DepthReport::new(options.width);
+        for depth in parse_depths(input) {
+            report.add(depth?);
+        }
+        let chart = report.chart(REPORT_CHART_HEIGHT);
+        let text = Grid::from_cells(
+            chart.width(),
+            chart.height(),
+            chart
+                .iter()
+                .map(|&seabed| if seabed { '#' } else { '.' })
+                .collect(),
+        );
+        let image = Grid::from_cells(
+            chart.width(),
+            chart.height(),
+            chart
+                .iter()
+                .map(|&seabed| if seabed { [110, 85, 50] } else { [30, 80, 160] })
+                .collect(),
+        );
+        Ok(Some(Rendering {
+            text: format!("{}\n{}", report, text),
+            image,
+            svg: None,
+        }))
+    }
+}
+
+/// How many rows deep Day1::render draws the depth profile.
+const REPORT_CHART_HEIGHT: usize = 16;
+
+/// Counts the windows of `size` depths whose sum is more than the sum of the
+/// window before. The depths are parsed as they're counted.
+fn count_increases(input: &str, size: usize) -> Result<Answer, PuzzleError> {
+    let mut err = None;
+    let depths = parse_depths(input).map_while(|depth| match depth {
+        Ok(depth) => Some(depth),
+        Err(e) => {
+            err = Some(e);
+            None
+        }
+    });
+    let increases = window_changes(depths, size, DepthChange::Increase).count();
+    match err {
+        Some(err) => Err(err.into()),
+        None => Ok(Answer::number(increases as i64)),
+    }
+}
+
+/// Parses whitespace-separated depths one at a time, without collecting
+/// them.
+pub fn parse_depths(input: &str) -> impl Iterator<Item = Result<i64, ParseError>> + '_ {
+    input.split_ascii_whitespace().map(move |word| {
+        word.parse::<i64>()
+            .map_err(|_| ParseError::at_str(input, word, "integer"))
+    })
+}
+
+/// Reads whitespace-separated depths from `reader` one at a time, holding
+/// only the current line, so logs too big to load can be streamed into
+/// window_changes or a DepthReport. Errors give the line and column of the
+/// bad depth. After a read error, nothing more is read.
+pub fn read_depths<R: BufRead>(reader: R) -> ReadDepths<R> {
+    ReadDepths {
+        reader: Some(reader),
+        line: String::new(),
+        line_number: 0,
+        pos: 0,
+    }
+}
+
+/// An iterator over the depths read from a BufRead. See read_depths.
+pub struct ReadDepths<R> {
+    /// None once reading has failed.
+    reader: Option<R>,
+
+    /// The line being parsed, its number counting from 1, and the byte
+    /// offset of the rest of it.
+    line: String,
+    line_number: usize,
+    pos: usize,
+}
+
+impl<R: BufRead> Iterator for ReadDepths<R> {
+    type Item = Result<i64, ReadDepthsError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(word) = self.line[self.pos..].split_ascii_whitespace().next() {
+                self.pos = offset_in(&self.line, word) + word.len();
+                return Some(word.parse::<i64>().map_err(|_| {
+                    let mut err = ParseError::at_str(&self.line, word, "integer");
+                    err.line = self.line_number;
+                    ReadDepthsError::Parse(err)
+                }));
+            }
+            let reader = self.reader.as_mut()?;
+            self.line.clear();
+            self.pos = 0;
+            match reader.read_line(&mut self.line) {
+                Ok(0) => return None,
+                Ok(_) => self.line_number += 1,
+                Err(err) => {
+                    self.reader = None;
+                    return Some(Err(ReadDepthsError::Io(err)));
+                }
+            }
+        }
+    }
+}
+
+/// An error reading depths with read_depths.
+#[derive(Debug)]
+pub enum ReadDepthsError {
+    /// The reader failed, or the text wasn't UTF-8.
+    Io(io::Error),
+
+    /// A depth wasn't an integer.
+    Parse(ParseError),
+}
+
+impl Display for ReadDepthsError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadDepthsError::Io(err) => write!(f, "reading depths: {}", err),
+            ReadDepthsError::Parse(err) => err.fmt(f),
+        }
+    }
+}
+
+impl Error for ReadDepthsError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ReadDepthsError::Io(err) => Some(err),
+            ReadDepthsError::Parse(err) => Some(err),
+        }
+    }
+}
+
+/// How the sum of one window of depths compares to the window before.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DepthChange {
+    Increase,
+    Decrease,
+    Unchanged,
+}
+
+impl DepthChange {
+    fn between(before: i64, after: i64) -> DepthChange {
+        match after.cmp(&before) {
+            Ordering::Greater => DepthChange::Increase,
+            Ordering::Less => DepthChange::Decrease,
+            Ordering::Equal => DepthChange::Unchanged,
+        }
+    }
+}
+
+/// Returns the positions where the sum of a sliding window of `size` depths
+/// changes the way `change` says, compared to the window one depth earlier.
+/// A window's position is the index of its last depth, counting from 0, so
+/// the first position that can be reported is `size`. Only the last `size`
+/// depths are kept, so `depths` can be arbitrarily long.
+///
+/// Panics if `size` is 0.
+pub fn window_changes<I>(depths: I, size: usize, change: DepthChange) -> WindowChanges<I::IntoIter>
+where
+    I: IntoIterator<Item = i64>,
+{
+    assert!(size > 0, "window size must be at least 1");
+    WindowChanges {
+        depths: depths.into_iter(),
+        window: VecDeque::with_capacity(size + 1),
+        size,
+        change,
+        position: 0,
+    }
+}
+
+/// An iterator over the positions of windows of depths that changed a
+/// particular way. See window_changes.
+pub struct WindowChanges<I> {
+    depths: I,
+
+    /// The depths in the current window, oldest first.
+    window: VecDeque<i64>,
+    size: usize,
+    change: DepthChange,
+
+    /// Index of the next depth to be read.
+    position: usize,
+}
+
+impl<I: Iterator<Item = i64>> Iterator for WindowChanges<I> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        for depth in self.depths.by_ref() {
+            let position = self.position;
+            self.position += 1;
+            self.window.push_back(depth);
+            if self.window.len() <= self.size {
+                continue;
+            }
+            // A window shares all but its last depth with the window before,
+            // which had this one's oldest depth instead, so comparing those
+            // two depths compares the sums.
+            let oldest = self.window.pop_front().unwrap();
+            if DepthChange::between(oldest, depth) == self.change {
+                return Some(position);
+            }
+        }
+        None
+    }
+}
+
+/// Statistics about a stream of depths, gathered one depth at a time so the
+/// depths never need to be held all at once.
+#[derive(Clone, Debug)]
+pub struct DepthReport {
+    /// How many depths there were.
+    pub count: usize,
+
+    /// The shallowest and deepest depths, or None if there were no depths.
+    pub min: Option<i64>,
+    pub max: Option<i64>,
+
+    /// The earliest of the longest runs of consecutive increases, or None
+    /// if the depth never increased.
+    pub longest_rise: Option<Rise>,
+
+    /// The earliest of the largest increases and decreases from one depth
+    /// to the next, or None if there weren't any.
+    pub largest_jump: Option<Step>,
+    pub largest_drop: Option<Step>,
+
+    sum: i128,
+    last: Option<i64>,
+
+    /// The run of increases ending at the last depth, if it increased.
+    rise: Option<Rise>,
+    profile: Profile,
+}
+
+/// A run of consecutive increases in depth.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rise {
+    /// Position of the depth the run starts from, counting from 0.
+    pub start: usize,
+
+    /// How many increases there are in the run.
+    pub len: usize,
+}
+
+/// A change in depth from one depth to the next.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Step {
+    /// Position of the depth after the change, counting from 0.
+    pub position: usize,
+
+    /// How far the depth changed.
+    pub amount: u64,
+}
+
+impl DepthReport {
+    /// Returns a report on no depths, whose profile will be drawn at most
+    /// `columns` wide.
+    pub fn new(columns: usize) -> DepthReport {
+        DepthReport {
+            count: 0,
+            min: None,
+            max: None,
+            longest_rise: None,
+            largest_jump: None,
+            largest_drop: None,
+            sum: 0,
+            last: None,
+            rise: None,
+            profile: Profile::new(columns),
+        }
+    }
+
+    /// Adds the next depth to the report.
+    pub fn add(&mut self, depth: i64) {
+        let position = self.count;
+        self.count += 1;
+        self.sum += depth as i128;
+        self.min = Some(self.min.map_or(depth, |min| min.min(depth)));
+        self.max = Some(self.max.map_or(depth, |max| max.max(depth)));
+        self.profile.add(depth);
+
+        if let Some(last) = self.last {
+            let step = Step {
+                position,
+                amount: depth.abs_diff(last),
+            };
+            let largest = match DepthChange::between(last, depth) {
+                DepthChange::Increase => Some(&mut self.largest_jump),
+                DepthChange::Decrease => Some(&mut self.largest_drop),
+                DepthChange::Unchanged => None,
+            };
+            if let Some(largest) = largest {
+                if largest.is_none_or(|largest| step.amount > largest.amount) {
+                    *largest = Some(step);
+                }
+            }
+
+            self.rise = match (depth > last, self.rise) {
+                (false, _) => None,
+                (true, None) => Some(Rise {
+                    start: position - 1,
+                    len: 1,
+                }),
+                (true, Some(rise)) => Some(Rise {
+                    len: rise.len + 1,
+                    ..rise
+                }),
+            };
+            if let Some(rise) = self.rise {
+                if self
+                    .longest_rise
+                    .is_none_or(|longest| rise.len > longest.len)
+                {
+                    self.longest_rise = Some(rise);
+                }
+            }
+        }
+        self.last = Some(depth);
+    }
+
+    /// Returns the mean depth, or None if there were no depths.
+    pub fn mean(&self) -> Option<f64> {
+        match self.count {
+            0 => None,
+            n => Some(self.sum as f64 / n as f64),
+        }
+    }
+
+    /// Draws the depth profile on one line, with a taller bar for deeper
+    /// water. Each character stands for the mean of a stretch of depths.
+    pub fn sparkline(&self) -> String {
+        const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        self.profile
+            .levels(BARS.len())
+            .map(|level| BARS[level])
+            .collect()
+    }
+
+    /// Draws the depth profile as a cross-section of the sea `height` rows
+    /// deep, shallowest at the top. Cells are true for seabed and false for
+    /// water. Each column stands for the mean of a stretch of depths.
+    pub fn chart(&self, height: usize) -> Grid<bool> {
+        let levels: Vec<usize> = self.profile.levels(height).collect();
+        let mut chart = Grid::new(levels.len(), height, false);
+        for (x, level) in levels.into_iter().enumerate() {
+            for y in level..height {
+                chart[(x, y)] = true;
+            }
+        }
+        chart
+    }
+}
+
+impl Display for DepthReport {
+    /// Formats the report as one statistic per line, followed by the
+    /// sparkline.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "depths: {}", self.count)?;
+        let (Some(min), Some(max), Some(mean)) = (self.min, self.max, self.mean()) else {
+            return Ok(());
+        };
+        writeln!(f, "min: {}, max: {}, mean: {:.1}", min, max, mean)?;
+        match self.longest_rise {
+            Some(rise) => writeln!(
+                f,
+                "longest rise: {} increases from position {}",
+                rise.len, rise.start
+            )?,
+            None => writeln!(f, "longest rise: none")?,
+        }
+        for (name, step) in [("jump", self.largest_jump), ("drop", self.largest_drop)] {
+            match step {
+                Some(step) => writeln!(
+                    f,
+                    "largest {}: {} at position {}",
+                    name, step.amount, step.position
+                )?,
+                None => writeln!(f, "largest {}: none", name)?,
+            }
+        }
+        writeln!(f, "{}", self.sparkline())
+    }
+}
+
+/// The mean depths of consecutive stretches of depths, for drawing. When
+/// there are more stretches than columns, neighboring pairs are merged, so
+/// any number of depths fits in between half the columns and all of them.
+#[derive(Clone, Debug)]
+struct Profile {
+    /// The sum and count of the depths in each stretch.
+    stretches: Vec<(i128, usize)>,
+    columns: usize,
+
+    /// How many depths make up each full stretch.
+    stretch_len: usize,
+}
+
+impl Profile {
+    fn new(columns: usize) -> Profile {
+        Profile {
+            stretches: Vec::new(),
+            columns: columns.max(2),
+            stretch_len: 1,
+        }
+    }
+
+    fn add(&mut self, depth: i64) {
+        match self.stretches.last_mut() {
+            Some((sum, count)) if *count < self.stretch_len => {
+                *sum += depth as i128;
+                *count += 1;
+            }
+            _ => {
+                if self.stretches.len() == self.columns {
+                    self.merge();
+                    return self.add(depth);
+                }
+                self.stretches.push((depth as i128, 1));
+            }
+        }
+    }
+
+    /// Merges neighboring pairs of stretches, halving how many there are.
+    fn merge(&mut self) {
+        self.stretches = self
+            .stretches
+            .chunks(2)
+            .map(|pair| {
+                pair.iter()
+                    .fold((0, 0), |(s, n), (sum, count)| (s + sum, n + count))
+            })
+            .collect();
+        self.stretch_len *= 2;
+    }
+
+    /// Returns the mean depth of each stretch, scaled to a level from 0 for
+    /// the shallowest up to `levels` - 1 for the deepest.
+    fn levels(&self, levels: usize) -> impl Iterator<Item = usize> + '_ {
+        let means: Vec<f64> = self
+            .stretches
+            .iter()
+            .map(|&(sum, count)| sum as f64 / count as f64)
+            .collect();
+        let min = means.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = means.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let top = levels.saturating_sub(1) as f64;
+        means.into_iter().map(move |mean| match max > min {
+            true => ((mean - min) / (max - min) * top).round() as usize,
+            false => 0,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::DEPTHS;
+
+    #[test]
+    fn change_positions() {
+        let depths = || parse_depths(DEPTHS).map(Result::unwrap);
+        let positions = |size, change| window_changes(depths(), size, change).collect::<Vec<_>>();
+        assert_eq!(positions(1, DepthChange::Increase), [1, 2, 3, 5, 6, 7, 9]);
+        assert_eq!(positions(1, DepthChange::Decrease), [4, 8]);
+        assert_eq!(positions(3, DepthChange::Increase), [3, 6, 7, 8, 9]);
+        assert_eq!(positions(3, DepthChange::Decrease), [5]);
+        assert_eq!(positions(3, DepthChange::Unchanged), [4]);
+    }
+
+    #[test]
+    fn report() {
+        let mut report = DepthReport::new(80);
+        for depth in parse_depths(DEPTHS) {
+            report.add(depth.unwrap());
+        }
+        assert_eq!(
+            (report.count, report.min, report.max),
+            (10, Some(199), Some(269))
+        );
+        assert_eq!(report.mean(), Some(225.6));
+        assert_eq!(report.longest_rise, Some(Rise { start: 0, len: 3 }));
+        let step = |position, amount| Some(Step { position, amount });
+        assert_eq!(report.largest_jump, step(6, 33));
+        assert_eq!(report.largest_drop, step(4, 10));
+        assert_eq!(report.sparkline(), "▁▁▂▂▁▂▅█▇▇");
+
+        // Squeezed into fewer columns, each one is the mean of a few depths.
+        let mut narrow = DepthReport::new(4);
+        for depth in parse_depths(DEPTHS) {
+            narrow.add(depth.unwrap());
+        }
+        assert_eq!(narrow.sparkline(), "▁▄█");
+
+        let err = parse_depths("199\n200\n2o8\n")
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!((err.line, err.column), (3, 1));
+    }
+
+    #[test]
+    fn read_depths_from_a_reader() {
+        // A tiny buffer makes lines arrive in pieces.
+        let reader = io::BufReader::with_capacity(4, DEPTHS.as_bytes());
+        let depths = read_depths(reader).map(Result::unwrap);
+        let positions: Vec<usize> = window_changes(depths, 3, DepthChange::Increase).collect();
+        assert_eq!(positions, [3, 6, 7, 8, 9]);
+
+        let mut report = DepthReport::new(80);
+        for depth in read_depths("  199 200\n\n\t208\n".as_bytes()) {
+            report.add(depth.unwrap());
+        }
+        assert_eq!(
+            (report.count, report.min, report.max),
+            (3, Some(199), Some(208))
+        );
+
+        let results: Vec<_> = read_depths("199\n\n200 2o8 210\n".as_bytes()).collect();
+        assert_eq!(results.len(), 4);
+        let Err(ReadDepthsError::Parse(err)) = &results[2] else {
+            panic!("{:?}", results[2]);
+        };
+        assert_eq!((err.line, err.column, err.found.as_str()), (3, 5, "'2o8'"));
+        assert_eq!(err.snippet(), "3 | 200 2o8 210\n  |     ^^^\n");
+        assert!(matches!(results[3], Ok(210)));
+    }
+
+    #[test]
+    fn read_depths_stops_after_a_read_error() {
+        struct Failing;
+        impl io::Read for Failing {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                Err(io::Error::other("disk on fire"))
+            }
+        }
+        let reader = io::BufReader::new(io::Read::chain("199\n".as_bytes(), Failing));
+        let results: Vec<_> = read_depths(reader).collect();
+        assert_eq!(results.len(), 2);
+        assert!(matches!(results[0], Ok(199)));
+        assert!(matches!(results[1], Err(ReadDepthsError::Io(_))));
+
+        let results: Vec<_> = read_depths(&b"199\n\xff\n"[..]).collect();
+        assert!(matches!(
+            results[..],
+            [Ok(199), Err(ReadDepthsError::Io(_))]
+        ));
+    }
+}
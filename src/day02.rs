@@ -0,0 +1,619 @@
+//! Day 2: Dive!.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::fmt::Write;
+
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+use crate::RenderOptions;
+use crate::Rendering;
+
+pub struct Day2;
+
+impl Day for Day2 {
+    const DAY: u32 = 2;
+    const TITLE: &'static str = "Dive!";
+    type Input = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(parse_instructions(input)?)
+    }
+
+    fn part1(course: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle2_answer(Submarine::new().validate(&course, Plain))
+    }
+
+    fn part2(course: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle2_answer(Submarine::new().validate(&course, Aimed))
+    }
+
+    /// Draws the course seen from the side, optionally with the other
+    /// part's course over it.
+    fn render(
+        part: u32,
+        course: &Self::Input,
+        options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError> {
+        let parts = match (options.overlay, part) {
+            (true, _) => vec![1, 2],
+            (false, part) => vec![part],
+        };
+        let mut plot = CoursePlot::new();
+        for part in parts {
+            // trajectory doesn't check for overflow, so the course is
+            // validated first.
+            let points = match part {
+                1 => Submarine::new()
+                    .validate(course, Plain)
+                    .map(|_| trajectory(course, Plain)),
+                _ => Submarine::new()
+                    .validate(course, Aimed)
+                    .map(|_| trajectory(course, Aimed)),
+            };
+            plot.add(format!("part {}", part), points.map_err(puzzle2_violation)?);
+        }
+        let image = plot.cells(PLOT_IMAGE_SIZE.0, PLOT_IMAGE_SIZE.1);
+        let image = Grid::from_cells(
+            image.width(),
+            image.height(),
+            image
+                .iter()
+                .map(|&courses| match courses.count_ones() {
+                    0 => [255, 255, 255],
+                    1 => PLOT_COLORS[courses.trailing_zeros() as usize].1,
+                    _ => [0, 0, 0],
+                })
+                .collect(),
+        );
+        Ok(Some(Rendering {
+            text: plot.ascii(options.width, PLOT_TEXT_HEIGHT),
+            image,
+            svg: Some(plot.svg()),
+        }))
+    }
+}
+
+/// Sizes of the plots drawn by Day2::render. The text is as wide as there's
+/// room for.
+const PLOT_TEXT_HEIGHT: usize = 20;
+const PLOT_IMAGE_SIZE: (usize, usize) = (800, 400);
+
+fn puzzle2_answer(result: Result<Submarine, CourseViolation>) -> Result<Answer, PuzzleError> {
+    let submarine = result.map_err(puzzle2_violation)?;
+    let product = submarine
+        .hpos
+        .checked_mul(submarine.depth)
+        .ok_or_else(|| PuzzleError::invalid(format!("{} overflows", submarine)))?;
+    Ok(Answer::number(product)
+        .with("hpos", submarine.hpos)
+        .with("depth", submarine.depth))
+}
+
+fn puzzle2_violation(violation: CourseViolation) -> PuzzleError {
+    PuzzleError::invalid(violation.to_string()).at_line(violation.instruction.line)
+}
+
+const DAY2_COMMANDS: &str = "'forward', 'down', or 'up'";
+
+/// One command from the planned course.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SubmarineCommand {
+    Forward(i64),
+    Down(i64),
+    Up(i64),
+}
+
+impl Display for SubmarineCommand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmarineCommand::Forward(n) => write!(f, "forward {}", n),
+            SubmarineCommand::Down(n) => write!(f, "down {}", n),
+            SubmarineCommand::Up(n) => write!(f, "up {}", n),
+        }
+    }
+}
+
+/// A command along with the line of the course it came from, counting from 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Instruction {
+    pub line: usize,
+    pub command: SubmarineCommand,
+}
+
+/// Parses one command per line, like "forward 5". Blank lines are skipped.
+pub fn parse_instructions(input: &str) -> Result<Vec<Instruction>, ParseError> {
+    let mut instructions = Vec::new();
+    for (i, line) in input.lines().enumerate() {
+        let words: Vec<&str> = line.split_ascii_whitespace().collect();
+        if words.is_empty() {
+            continue;
+        }
+        if words.len() != 2 {
+            return Err(ParseError::at_str(input, line, "command and amount"));
+        }
+        let n = words[1]
+            .parse::<i64>()
+            .map_err(|_| ParseError::at_str(input, words[1], "integer amount"))?;
+        let command = match words[0] {
+            "forward" => SubmarineCommand::Forward(n),
+            "down" => SubmarineCommand::Down(n),
+            "up" => SubmarineCommand::Up(n),
+            _ => return Err(ParseError::at_str(input, words[0], DAY2_COMMANDS)),
+        };
+        instructions.push(Instruction {
+            line: i + 1,
+            command,
+        });
+    }
+    Ok(instructions)
+}
+
+/// Where the submarine is and which way it's pointing. Depth increases
+/// going down.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Submarine {
+    pub hpos: i64,
+    pub depth: i64,
+    pub aim: i64,
+}
+
+impl Display for Submarine {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "hpos {}, depth {}, aim {}",
+            self.hpos, self.depth, self.aim
+        )
+    }
+}
+
+/// A way of interpreting commands. Any closure taking the submarine and a
+/// command can be used as one.
+pub trait Strategy {
+    /// Updates `submarine` by carrying out `command`.
+    fn apply(&self, submarine: &mut Submarine, command: SubmarineCommand);
+
+    /// Returns the submarine after carrying out `command`, or None if that
+    /// overflows. By default this just calls apply, so strategies that can
+    /// overflow should check for it here.
+    fn checked_apply(&self, submarine: &Submarine, command: SubmarineCommand) -> Option<Submarine> {
+        let mut next = *submarine;
+        self.apply(&mut next, command);
+        Some(next)
+    }
+}
+
+impl<F: Fn(&mut Submarine, SubmarineCommand)> Strategy for F {
+    fn apply(&self, submarine: &mut Submarine, command: SubmarineCommand) {
+        self(submarine, command)
+    }
+}
+
+/// The first part's reading of the commands: down and up change the depth
+/// directly, and aim is never used.
+#[derive(Clone, Copy, Debug)]
+pub struct Plain;
+
+impl Strategy for Plain {
+    fn apply(&self, submarine: &mut Submarine, command: SubmarineCommand) {
+        match command {
+            SubmarineCommand::Forward(n) => submarine.hpos += n,
+            SubmarineCommand::Down(n) => submarine.depth += n,
+            SubmarineCommand::Up(n) => submarine.depth -= n,
+        }
+    }
+
+    fn checked_apply(&self, submarine: &Submarine, command: SubmarineCommand) -> Option<Submarine> {
+        let mut next = *submarine;
+        match command {
+            SubmarineCommand::Forward(n) => next.hpos = next.hpos.checked_add(n)?,
+            SubmarineCommand::Down(n) => next.depth = next.depth.checked_add(n)?,
+            SubmarineCommand::Up(n) => next.depth = next.depth.checked_sub(n)?,
+        }
+        Some(next)
+    }
+}
+
+/// The second part's reading of the commands: down and up change the aim,
+/// and moving forward changes the depth by the aim for each unit moved.
+#[derive(Clone, Copy, Debug)]
+pub struct Aimed;
+
+impl Strategy for Aimed {
+    fn apply(&self, submarine: &mut Submarine, command: SubmarineCommand) {
+        match command {
+            SubmarineCommand::Forward(n) => {
+                submarine.hpos += n;
+                submarine.depth += submarine.aim * n;
+            }
+            SubmarineCommand::Down(n) => submarine.aim += n,
+            SubmarineCommand::Up(n) => submarine.aim -= n,
+        }
+    }
+
+    fn checked_apply(&self, submarine: &Submarine, command: SubmarineCommand) -> Option<Submarine> {
+        let mut next = *submarine;
+        match command {
+            SubmarineCommand::Forward(n) => {
+                next.hpos = next.hpos.checked_add(n)?;
+                next.depth = next.depth.checked_add(next.aim.checked_mul(n)?)?;
+            }
+            SubmarineCommand::Down(n) => next.aim = next.aim.checked_add(n)?,
+            SubmarineCommand::Up(n) => next.aim = next.aim.checked_sub(n)?,
+        }
+        Some(next)
+    }
+}
+
+/// The submarine after carrying out one instruction, for following where
+/// a course goes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TraceStep {
+    pub instruction: Instruction,
+    pub submarine: Submarine,
+}
+
+impl Submarine {
+    /// Returns a submarine at the surface, at position 0 and aimed level.
+    pub fn new() -> Submarine {
+        Submarine::default()
+    }
+
+    /// Carries out one command.
+    pub fn step(&mut self, command: SubmarineCommand, strategy: &impl Strategy) {
+        strategy.apply(self, command);
+    }
+
+    /// Carries out every instruction in order, returning where the
+    /// submarine ends up.
+    pub fn run(mut self, course: &[Instruction], strategy: impl Strategy) -> Submarine {
+        for instruction in course {
+            self.step(instruction.command, &strategy);
+        }
+        self
+    }
+
+    /// Carries out every instruction in order like run, but stops at the
+    /// first one that overflows or takes the submarine above the surface.
+    pub fn validate(
+        mut self,
+        course: &[Instruction],
+        strategy: impl Strategy,
+    ) -> Result<Submarine, CourseViolation> {
+        for &instruction in course {
+            let violation = |kind| CourseViolation {
+                instruction,
+                submarine: self,
+                kind,
+            };
+            self = match strategy.checked_apply(&self, instruction.command) {
+                Some(next) if next.depth < 0 => return Err(violation(ViolationKind::AboveSurface)),
+                Some(next) => next,
+                None => return Err(violation(ViolationKind::Overflow)),
+            };
+        }
+        Ok(self)
+    }
+
+    /// Carries out every instruction in order, returning the submarine after
+    /// each one.
+    pub fn trace<'a>(
+        mut self,
+        course: &'a [Instruction],
+        strategy: impl Strategy + 'a,
+    ) -> impl Iterator<Item = TraceStep> + 'a {
+        course.iter().map(move |&instruction| {
+            self.step(instruction.command, &strategy);
+            TraceStep {
+                instruction,
+                submarine: self,
+            }
+        })
+    }
+}
+
+/// An instruction that the submarine couldn't safely carry out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CourseViolation {
+    pub instruction: Instruction,
+
+    /// The submarine just before the instruction.
+    pub submarine: Submarine,
+    pub kind: ViolationKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ViolationKind {
+    /// A position, depth or aim would be too big to store.
+    Overflow,
+
+    /// The depth would be negative.
+    AboveSurface,
+}
+
+impl Display for CourseViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let problem = match self.kind {
+            ViolationKind::Overflow => "overflows",
+            ViolationKind::AboveSurface => "takes the submarine above the surface",
+        };
+        write!(
+            f,
+            "'{}' {} (at {})",
+            self.instruction.command, problem, self.submarine
+        )
+    }
+}
+
+/// Returns the (hpos, depth) positions the submarine passes through when
+/// following the course, starting from where it starts.
+pub fn trajectory(course: &[Instruction], strategy: impl Strategy) -> Vec<(i64, i64)> {
+    let start = Submarine::new();
+    let mut points = vec![(start.hpos, start.depth)];
+    points.extend(
+        start
+            .trace(course, strategy)
+            .map(|step| (step.submarine.hpos, step.submarine.depth)),
+    );
+    points
+}
+
+/// The character and color each course in a plot is drawn with, in the
+/// order the courses were added.
+const PLOT_COLORS: [(char, [u8; 3], &str); 4] = [
+    ('#', [31, 119, 180], "#1f77b4"),
+    ('o', [214, 39, 40], "#d62728"),
+    ('+', [44, 160, 44], "#2ca02c"),
+    ('x', [148, 103, 189], "#9467bd"),
+];
+
+/// Where a cell in a text plot is crossed by more than one course.
+const PLOT_CROSSING: char = '*';
+
+/// A side view of one or more courses, drawn with the same scale so they can
+/// be compared. Depth increases downwards, as it does in the sea.
+#[derive(Clone, Debug, Default)]
+pub struct CoursePlot {
+    courses: Vec<(String, Vec<(i64, i64)>)>,
+}
+
+impl CoursePlot {
+    pub fn new() -> CoursePlot {
+        CoursePlot::default()
+    }
+
+    /// Adds a course, given as the points from trajectory.
+    ///
+    /// Panics if there are already as many courses as there are colors to
+    /// draw them with.
+    pub fn add(&mut self, label: impl Into<String>, points: Vec<(i64, i64)>) {
+        assert!(
+            self.courses.len() < PLOT_COLORS.len(),
+            "too many courses to plot"
+        );
+        self.courses.push((label.into(), points));
+    }
+
+    /// Returns the smallest and largest hpos and depth of any point, always
+    /// including the start.
+    fn bounds(&self) -> ((i64, i64), (i64, i64)) {
+        let points = self.courses.iter().flat_map(|(_, points)| points);
+        points.fold(((0, 0), (0, 0)), |((x0, x1), (y0, y1)), &(x, y)| {
+            ((x0.min(x), x1.max(x)), (y0.min(y), y1.max(y)))
+        })
+    }
+
+    /// Draws the courses on a grid of `width` by `height` cells. Each cell
+    /// has bit i set if the course added i-th passes through it.
+    pub fn cells(&self, width: usize, height: usize) -> Grid<u8> {
+        let mut cells = Grid::new(width, height, 0u8);
+        if width == 0 || height == 0 {
+            return cells;
+        }
+        let ((x0, x1), (y0, y1)) = self.bounds();
+        // Scales a coordinate from [lo, hi] to [0, cells - 1], in i128 so
+        // that it can't overflow.
+        let scale = |v: i64, lo: i64, hi: i64, cells: usize| -> f64 {
+            match hi > lo {
+                true => {
+                    (v as i128 - lo as i128) as f64 / (hi as i128 - lo as i128) as f64
+                        * (cells - 1) as f64
+                }
+                false => 0.0,
+            }
+        };
+        for (i, (_, points)) in self.courses.iter().enumerate() {
+            let to_cell =
+                |&(x, y): &(i64, i64)| (scale(x, x0, x1, width), scale(y, y0, y1, height));
+            let mut last = points.first().map(to_cell);
+            for point in points {
+                let (x, y) = to_cell(point);
+                let (lx, ly) = last.unwrap_or((x, y));
+                // Fill in every cell along the line from the last point.
+                let steps = (x - lx).abs().max((y - ly).abs()).ceil().max(1.0) as usize;
+                for step in 0..=steps {
+                    let t = step as f64 / steps as f64;
+                    let cx = (lx + (x - lx) * t).round() as usize;
+                    let cy = (ly + (y - ly) * t).round() as usize;
+                    cells[(cx, cy)] |= 1 << i;
+                }
+                last = Some((x, y));
+            }
+        }
+        cells
+    }
+
+    /// Draws the courses with characters, `width` wide and `height` high,
+    /// followed by a key saying which character is which course.
+    pub fn ascii(&self, width: usize, height: usize) -> String {
+        let cells = self.cells(width, height);
+        let plot = Grid::from_cells(
+            width,
+            height,
+            cells
+                .iter()
+                .map(|&courses| match courses.count_ones() {
+                    0 => '.',
+                    1 => PLOT_COLORS[courses.trailing_zeros() as usize].0,
+                    _ => PLOT_CROSSING,
+                })
+                .collect(),
+        );
+        let mut text = plot.to_string();
+        for ((label, _), (mark, _, _)) in self.courses.iter().zip(PLOT_COLORS) {
+            let _ = write!(text, "\n{} {}", mark, label);
+        }
+        if self.courses.len() > 1 {
+            let _ = write!(text, "\n{} more than one", PLOT_CROSSING);
+        }
+        text
+    }
+
+    /// Draws the courses as an SVG document, with a polyline for each course
+    /// and a key below. The plot is stretched to fill the picture, since
+    /// courses are usually much deeper than they are long.
+    pub fn svg(&self) -> String {
+        let (width, height) = PLOT_IMAGE_SIZE;
+        let key_height = 20 * self.courses.len();
+        let ((x0, x1), (y0, y1)) = self.bounds();
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}">"#,
+            width,
+            height + key_height
+        );
+        let _ = writeln!(
+            svg,
+            r#"<svg width="{}" height="{}" viewBox="{} {} {} {}" preserveAspectRatio="none">"#,
+            width,
+            height,
+            x0,
+            y0,
+            (x1 - x0).max(1),
+            (y1 - y0).max(1)
+        );
+        for ((label, points), (_, _, color)) in self.courses.iter().zip(PLOT_COLORS) {
+            let points: Vec<String> = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect();
+            let _ = writeln!(
+                svg,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="2" vector-effect="non-scaling-stroke"><title>{}</title></polyline>"#,
+                points.join(" "),
+                color,
+                svg_escape(label)
+            );
+        }
+        let _ = writeln!(svg, "</svg>");
+        for (i, ((label, _), (_, _, color))) in self.courses.iter().zip(PLOT_COLORS).enumerate() {
+            let _ = writeln!(
+                svg,
+                r#"<text x="4" y="{}" font-family="sans-serif" font-size="14" fill="{}">{}</text>"#,
+                height + 20 * i + 15,
+                color,
+                svg_escape(label)
+            );
+        }
+        let _ = writeln!(svg, "</svg>");
+        svg
+    }
+}
+
+/// Escapes the characters that can't appear as themselves in SVG text.
+fn svg_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::examples::COMMANDS;
+
+    #[test]
+    fn trace() {
+        let course = parse_instructions(COMMANDS).unwrap();
+        let states: Vec<(usize, i64, i64, i64)> = Submarine::new()
+            .trace(&course, Aimed)
+            .map(|step| {
+                let sub = step.submarine;
+                (step.instruction.line, sub.hpos, sub.depth, sub.aim)
+            })
+            .collect();
+        assert_eq!(
+            states,
+            [
+                (1, 5, 0, 0),
+                (2, 5, 0, 5),
+                (3, 13, 40, 5),
+                (4, 13, 40, 2),
+                (5, 13, 40, 10),
+                (6, 15, 60, 10),
+            ]
+        );
+
+        // A strategy where forward moves the submarine diagonally downwards.
+        let diagonal = |sub: &mut Submarine, command| match command {
+            SubmarineCommand::Forward(n) => {
+                sub.hpos += n;
+                sub.depth += n;
+            }
+            SubmarineCommand::Down(n) => sub.depth += n,
+            SubmarineCommand::Up(n) => sub.depth -= n,
+        };
+        let sub = Submarine::new().run(&course, diagonal);
+        assert_eq!((sub.hpos, sub.depth), (15, 25));
+    }
+
+    #[test]
+    fn violations() {
+        let course = parse_instructions("forward 2\ndown 3\n\nup 5\n").unwrap();
+        let violation = Submarine::new().validate(&course, Plain).unwrap_err();
+        assert_eq!(violation.kind, ViolationKind::AboveSurface);
+        assert_eq!(violation.instruction.line, 4);
+        assert_eq!(
+            (violation.submarine.hpos, violation.submarine.depth),
+            (2, 3)
+        );
+        assert_eq!(
+            violation.to_string(),
+            "'up 5' takes the submarine above the surface (at hpos 2, depth 3, aim 0)"
+        );
+        assert!(Submarine::new().validate(&course, Aimed).is_ok());
+
+        let course = parse_instructions("down 4611686018427387904\nforward 2\n").unwrap();
+        let violation = Submarine::new().validate(&course, Aimed).unwrap_err();
+        assert_eq!(violation.kind, ViolationKind::Overflow);
+        assert_eq!(violation.instruction.line, 2);
+    }
+
+    #[test]
+    fn plot() {
+        let course = parse_instructions(COMMANDS).unwrap();
+        assert_eq!(
+            trajectory(&course, Plain),
+            [(0, 0), (5, 0), (5, 5), (13, 5), (13, 2), (13, 10), (15, 10)]
+        );
+        let mut plot = CoursePlot::new();
+        plot.add("part 1", trajectory(&course, Plain));
+        plot.add("part 2", trajectory(&course, Aimed));
+        let want = "\
+*******#######..
+.......ooo...###
+..........ooo...
+.............oo.
+...............o
+# part 1
+o part 2
+* more than one";
+        assert_eq!(plot.ascii(16, 5), want);
+        let svg = plot.svg();
+        assert!(svg.contains(r#"viewBox="0 0 15 60""#), "{}", svg);
+        assert!(svg.contains(r#"<polyline points="0,0 5,0 5,5 13,5 13,2 13,10 15,10""#));
+    }
+}
@@ -0,0 +1,135 @@
+//! Day 3: Binary Diagnostic.
+
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day3;
+
+impl Day for Day3 {
+    const DAY: u32 = 3;
+    const TITLE: &'static str = "Binary Diagnostic";
+    type Input = (Vec<usize>, usize);
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(puzzle3_parse_input(input)?)
+    }
+
+    fn part1((values, width): Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle3_1(values, width)
+    }
+
+    fn part2((values, width): Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle3_2(values, width)
+    }
+}
+
+fn puzzle3_1(values: Vec<usize>, width: usize) -> Result<Answer, PuzzleError> {
+    let gamma_rate = puzzle3_most_common_bits(&values[..], width);
+    let mask = (1 << width) - 1;
+    let epsilon_rate = gamma_rate ^ mask;
+    let power = gamma_rate * epsilon_rate;
+    Ok(Answer::number(power as i64)
+        .with("gamma_rate", gamma_rate as i64)
+        .with("epsilon_rate", epsilon_rate as i64))
+}
+
+fn puzzle3_2(values: Vec<usize>, width: usize) -> Result<Answer, PuzzleError> {
+    let mut o2_generator_candidates = values.clone();
+    for i in 0..width {
+        let most_common = puzzle3_most_common_bits(&o2_generator_candidates[..], width);
+        let mask = 1 << (width - i - 1);
+        o2_generator_candidates = o2_generator_candidates
+            .iter()
+            .copied()
+            .filter(|v| ((*v ^ most_common) & mask) == 0)
+            .collect();
+        if o2_generator_candidates.len() <= 1 {
+            break;
+        }
+    }
+    if o2_generator_candidates.len() != 1 {
+        return Err(PuzzleError::no_solution(
+            "did not find exactly one O2 generator candidate",
+        ));
+    }
+    let o2_generator_rating = o2_generator_candidates[0];
+
+    let mut co2_scrubber_candidates = values;
+    for i in 0..width {
+        let most_common = puzzle3_most_common_bits(&co2_scrubber_candidates[..], width);
+        let least_common = most_common ^ ((1 << width) - 1);
+        let mask = 1 << (width - i - 1);
+        co2_scrubber_candidates = co2_scrubber_candidates
+            .iter()
+            .copied()
+            .filter(|v| ((*v ^ least_common) & mask) == 0)
+            .collect();
+        if co2_scrubber_candidates.len() <= 1 {
+            break;
+        }
+    }
+    if co2_scrubber_candidates.len() != 1 {
+        return Err(PuzzleError::no_solution(
+            "did not find exactly one CO2 scrubber candidate",
+        ));
+    }
+    let co2_scrubber_rating = co2_scrubber_candidates[0];
+
+    let life_support_rating = o2_generator_rating * co2_scrubber_rating;
+    Ok(Answer::number(life_support_rating as i64)
+        .with("o2_generator_rating", o2_generator_rating as i64)
+        .with("co2_scrubber_rating", co2_scrubber_rating as i64))
+}
+
+fn puzzle3_parse_input(input: &str) -> Result<(Vec<usize>, usize), ParseError> {
+    let mut values: Vec<usize> = Vec::new();
+    let mut width = 0;
+    for line in input.lines() {
+        if line.is_empty() {
+            continue;
+        }
+        if width == 0 {
+            width = line.len();
+            if width >= usize::BITS as usize {
+                let expected = format!("at most {} bits", usize::BITS - 1);
+                return Err(ParseError::at_str(input, line, expected));
+            }
+        } else if width != line.len() {
+            let expected = format!("{} bits like earlier lines", width);
+            return Err(ParseError::at_str(input, line, expected));
+        }
+        let mut value = 0;
+        for (i, c) in line.char_indices() {
+            value = match c {
+                '0' => value << 1,
+                '1' => value << 1 | 1,
+                _ => return Err(ParseError::at(input, offset_in(input, line) + i, "0 or 1")),
+            };
+        }
+        values.push(value);
+    }
+    Ok((values, width))
+}
+
+fn puzzle3_most_common_bits(values: &[usize], width: usize) -> usize {
+    let mut counts = vec![0; width];
+    for value in values {
+        for (i, count) in counts.iter_mut().enumerate() {
+            if (value & (1 << (width - i - 1))) != 0 {
+                *count += 1;
+            }
+        }
+    }
+
+    let mut most_common = 0;
+    let half = values.len().div_ceil(2);
+    for (i, count) in counts.iter().enumerate() {
+        if *count >= half {
+            most_common |= 1 << (width - i - 1);
+        }
+    }
+    most_common
+}
@@ -0,0 +1,146 @@
+//! Day 4: Giant Squid.
+
+use crate::grid::Grid;
+use crate::parse::parse_separated;
+use crate::parse::parse_space_separated;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day4;
+
+impl Day for Day4 {
+    const DAY: u32 = 4;
+    const TITLE: &'static str = "Giant Squid";
+    type Input = Puzzle4Input;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(puzzle4_parse_input(input)?)
+    }
+
+    fn part1(input: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle4_1(input)
+    }
+
+    fn part2(input: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle4_2(input)
+    }
+}
+
+fn puzzle4_1(mut parsed: Puzzle4Input) -> Result<Answer, PuzzleError> {
+    for n in parsed.numbers.iter() {
+        for i in 0..parsed.boards.len() {
+            if let Some(score) = parsed.boards[i].mark(*n) {
+                return Ok(Answer::number(score)
+                    .with("winning_index", i as i64)
+                    .with("number", *n));
+            }
+        }
+    }
+
+    Err(PuzzleError::no_solution(
+        "all numbers called and nobody won",
+    ))
+}
+
+fn puzzle4_2(mut parsed: Puzzle4Input) -> Result<Answer, PuzzleError> {
+    let play = |b: &mut BingoBoard| {
+        for (i, n) in parsed.numbers.iter().enumerate() {
+            if let Some(score) = b.mark(*n) {
+                return (i, *n, score);
+            }
+        }
+        (parsed.numbers.len(), -1, -1)
+    };
+    let (board_index, (move_count, n, score)) = parsed
+        .boards
+        .iter_mut()
+        .map(play)
+        .enumerate()
+        .max_by(|(_, (lmoves, _, _)), (_, (rmoves, _, _))| lmoves.cmp(rmoves))
+        .ok_or_else(|| PuzzleError::invalid("no bingo boards"))?;
+    if move_count == parsed.numbers.len() {
+        return Err(PuzzleError::no_solution(format!(
+            "board {} never wins",
+            board_index
+        )));
+    }
+    Ok(Answer::number(score)
+        .with("board_index", board_index as i64)
+        .with("move_count", move_count as i64)
+        .with("number", n))
+}
+
+fn puzzle4_parse_input(input: &str) -> Result<Puzzle4Input, ParseError> {
+    let mut lines = input.lines();
+    let numbers = match lines.next() {
+        Some(line) => parse_separated::<i64>(line, ",").map_err(|e| e.within(input, line))?,
+        None => return Err(ParseError::at(input, 0, "numbers to call")),
+    };
+
+    let mut boards = Vec::<BingoBoard>::new();
+    'board_loop: loop {
+        match lines.next() {
+            None => break,
+            Some("") => (),
+            Some(line) => return Err(ParseError::at_str(input, line, "blank line")),
+        }
+        let mut squares = Vec::<i64>::with_capacity(25);
+        for row in 0..5 {
+            let line = match lines.next() {
+                None | Some("") if row == 0 => break 'board_loop,
+                None => return Err(ParseError::at(input, input.len(), "bingo board row")),
+                Some(line) if line.is_empty() => {
+                    return Err(ParseError::at_str(input, line, "bingo board row"))
+                }
+                Some(line) => line,
+            };
+            let square_numbers =
+                parse_space_separated::<i64>(line).map_err(|e| e.within(input, line))?;
+            if square_numbers.len() != 5 {
+                return Err(ParseError::at_str(input, line, "5 numbers"));
+            }
+            squares.extend(square_numbers);
+        }
+        boards.push(BingoBoard {
+            squares: Grid::from_cells(5, 5, squares),
+            marked: Grid::new(5, 5, false),
+        })
+    }
+
+    Ok(Puzzle4Input { numbers, boards })
+}
+
+pub struct Puzzle4Input {
+    pub numbers: Vec<i64>,
+    pub boards: Vec<BingoBoard>,
+}
+
+pub struct BingoBoard {
+    squares: Grid<i64>,
+    marked: Grid<bool>,
+}
+
+impl BingoBoard {
+    pub fn mark(&mut self, n: i64) -> Option<i64> {
+        for (square, marked) in self.squares.iter().zip(self.marked.iter_mut()) {
+            if *square == n {
+                *marked = true
+            }
+        }
+        if !self.marked.rows().any(|row| row.iter().all(|m| *m))
+            && !self.marked.columns().any(|mut column| column.all(|m| *m))
+        {
+            return None;
+        }
+
+        let mut unmarked_sum = 0;
+        for (square, marked) in self.squares.iter().zip(self.marked.iter()) {
+            if !marked {
+                unmarked_sum += square
+            }
+        }
+        Some(unmarked_sum * n)
+    }
+}
@@ -0,0 +1,259 @@
+//! Day 5: Hydrothermal Venture.
+
+use std::fmt;
+
+use crate::grid::Grid;
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day5;
+
+impl Day for Day5 {
+    const DAY: u32 = 5;
+    const TITLE: &'static str = "Hydrothermal Venture";
+    type Input = Vec<LineSegment>;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(puzzle5_parse_input(input)?)
+    }
+
+    fn part1(segments: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle5_1(segments)
+    }
+
+    fn part2(segments: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle5_2(segments)
+    }
+}
+
+fn puzzle5_1(parsed: Vec<LineSegment>) -> Result<Answer, PuzzleError> {
+    let mut floor_map = puzzle5_floor_map(&parsed)?;
+
+    for (i, line) in parsed.into_iter().enumerate() {
+        if line.a.x == line.b.x || line.a.y == line.b.y {
+            floor_map
+                .add_line(line)
+                .map_err(|msg| PuzzleError::invalid(msg).at_line(i + 1))?;
+        }
+    }
+
+    Ok(Answer::number(floor_map.danger_points() as i64))
+}
+
+fn puzzle5_2(parsed: Vec<LineSegment>) -> Result<Answer, PuzzleError> {
+    let mut floor_map = puzzle5_floor_map(&parsed)?;
+
+    for (i, line) in parsed.into_iter().enumerate() {
+        floor_map
+            .add_line(line)
+            .map_err(|msg| PuzzleError::invalid(msg).at_line(i + 1))?;
+    }
+
+    Ok(Answer::number(floor_map.danger_points() as i64))
+}
+
+/// The most cells a floor map may have. Real inputs need about a million.
+const MAX_FLOOR_CELLS: usize = 1 << 24;
+
+/// Returns an empty floor map big enough for every line segment.
+fn puzzle5_floor_map(segments: &[LineSegment]) -> Result<FloorMap, PuzzleError> {
+    let max_x = segments
+        .iter()
+        .fold(0, |x, line| x.max(line.a.x.max(line.b.x)));
+    let max_y = segments
+        .iter()
+        .fold(0, |y, line| y.max(line.a.y.max(line.b.y)));
+    let size = |max: i64| usize::try_from(max).ok()?.checked_add(1);
+    match (size(max_x), size(max_y)) {
+        (Some(width), Some(height))
+            if width
+                .checked_mul(height)
+                .is_some_and(|cells| cells <= MAX_FLOOR_CELLS) =>
+        {
+            Ok(FloorMap::new(width, height))
+        }
+        _ => Err(PuzzleError::invalid(format!(
+            "floor of {} by {} is too big",
+            max_x as i128 + 1,
+            max_y as i128 + 1
+        ))),
+    }
+}
+
+fn puzzle5_parse_input<'a>(input: &'a str) -> Result<Vec<LineSegment>, ParseError> {
+    let expect = |s: &'a str, want: &str| -> Result<&'a str, ParseError> {
+        let s = s.trim_start_matches(' ');
+        match s.strip_prefix(want) {
+            Some(rest) => Ok(rest),
+            None if want == "\n" => Err(ParseError::at(input, offset_in(input, s), "end of line")),
+            None => Err(ParseError::at(
+                input,
+                offset_in(input, s),
+                format!("'{}'", want),
+            )),
+        }
+    };
+
+    let parse_int = |s: &'a str| -> Result<(i64, &'a str), ParseError> {
+        let s = s.trim_start_matches(' ');
+        let end = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        match s[..end].parse::<i64>() {
+            Ok(n) => Ok((n, &s[end..])),
+            Err(_) => Err(ParseError::at_str(input, &s[..end], "integer")),
+        }
+    };
+
+    let parse_point = |s: &'a str| -> Result<(Point, &'a str), ParseError> {
+        let (x, s) = parse_int(s)?;
+        let s = expect(s, ",")?;
+        let (y, s) = parse_int(s)?;
+        Ok((Point { x, y }, s))
+    };
+
+    let parse_line = |s: &'a str| -> Result<(LineSegment, &'a str), ParseError> {
+        let (a, s) = parse_point(s)?;
+        let s = expect(s, "->")?;
+        let (b, s) = parse_point(s)?;
+        Ok((LineSegment { a, b }, s))
+    };
+
+    let mut segments = Vec::<LineSegment>::new();
+    let mut s = input;
+    while !s.is_empty() {
+        let line_and_s = parse_line(s)?;
+        segments.push(line_and_s.0);
+        s = line_and_s.1;
+        if !s.is_empty() {
+            s = expect(s, "\n")?;
+        }
+    }
+    Ok(segments)
+}
+
+pub struct LineSegment {
+    pub a: Point,
+    pub b: Point,
+}
+
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+pub struct FloorMap {
+    vents: Grid<i64>,
+}
+
+impl FloorMap {
+    pub fn new(width: usize, height: usize) -> FloorMap {
+        FloorMap {
+            vents: Grid::new(width, height, 0),
+        }
+    }
+
+    /// Adds a line to the map. The line must be horizontal, vertical, or
+    /// diagonal at exactly 45 degrees.
+    pub fn add_line(&mut self, line: LineSegment) -> Result<(), String> {
+        let len: usize;
+        let x_begin = line.a.x as isize;
+        let x_inc: isize;
+        let y_begin = line.a.y as isize;
+        let y_inc: isize;
+        if line.a.y == line.b.y {
+            // horizontal
+            if line.a.x <= line.b.x {
+                len = (line.b.x - line.a.x + 1) as usize;
+                x_inc = 1;
+            } else {
+                len = (line.a.x - line.b.x + 1) as usize;
+                x_inc = -1;
+            }
+            y_inc = 0;
+        } else if line.a.x == line.b.x {
+            // vertical
+            if line.a.y <= line.b.y {
+                len = (line.b.y - line.a.y + 1) as usize;
+                y_inc = 1;
+            } else {
+                len = (line.a.y - line.b.y + 1) as usize;
+                y_inc = -1;
+            }
+            x_inc = 0;
+        } else {
+            // diagonal
+            if line.a.x <= line.b.x {
+                len = (line.b.x - line.a.x + 1) as usize;
+                x_inc = 1;
+            } else {
+                len = (line.a.x - line.b.x + 1) as usize;
+                x_inc = -1;
+            }
+            let ylen: usize;
+            if line.a.y <= line.b.y {
+                ylen = (line.b.y - line.a.y + 1) as usize;
+                y_inc = 1;
+            } else {
+                ylen = (line.a.y - line.b.y + 1) as usize;
+                y_inc = -1;
+            }
+            if len != ylen {
+                return Err(String::from("diagonal line is not at 45 degrees"));
+            }
+        }
+
+        let mut x = x_begin;
+        let mut y = y_begin;
+        for _ in 0..len {
+            match self.vents.get_mut(x as usize, y as usize) {
+                Some(n) => *n += 1,
+                None => return Err(String::from("line extends outside the map")),
+            }
+            x += x_inc;
+            y += y_inc;
+        }
+        Ok(())
+    }
+
+    pub fn danger_points(&self) -> usize {
+        self.vents.iter().filter(|&n| *n >= 2).count()
+    }
+}
+
+impl fmt::Display for FloorMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        let mut sep = "";
+        for row in self.vents.rows() {
+            s.push_str(sep);
+            sep = "\n";
+            for &n in row {
+                if n == 0 {
+                    s.push('.')
+                } else if n <= 9 {
+                    s.push((b'0' + n as u8) as char)
+                } else {
+                    s.push('!')
+                }
+            }
+        }
+        f.write_str(s.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleErrorKind;
+
+    #[test]
+    fn huge_floor_is_rejected() {
+        for input in ["0,0 -> 4294967296,0\n", "0,0 -> 9223372036854775807,9\n"] {
+            let segments = Day5::parse(input).unwrap();
+            let err = Day5::part2(segments).unwrap_err();
+            assert_eq!(err.kind(), PuzzleErrorKind::Invalid, "{}", err);
+        }
+    }
+}
@@ -0,0 +1,105 @@
+//! Day 6: Lanternfish.
+
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day6;
+
+impl Day for Day6 {
+    const DAY: u32 = 6;
+    const TITLE: &'static str = "Lanternfish";
+    type Input = Lanternfish;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.trim_end().parse::<Lanternfish>()?)
+    }
+
+    fn part1(lanternfish: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle6_1(lanternfish)
+    }
+
+    fn part2(lanternfish: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle6_2(lanternfish)
+    }
+}
+
+fn puzzle6_1(mut lanternfish: Lanternfish) -> Result<Answer, PuzzleError> {
+    let days = 80;
+    for _ in 0..days {
+        lanternfish.advance();
+    }
+    let population = lanternfish
+        .population()
+        .ok_or_else(|| PuzzleError::no_solution("population too large to count"))?;
+    Ok(Answer::number(population as i64).with("days", days))
+}
+
+fn puzzle6_2(mut lanternfish: Lanternfish) -> Result<Answer, PuzzleError> {
+    let days = 256;
+    for _ in 0..days {
+        lanternfish.advance();
+    }
+    let population = lanternfish
+        .population()
+        .ok_or_else(|| PuzzleError::no_solution("population too large to count"))?;
+    Ok(Answer::number(population as i64).with("days", days))
+}
+
+pub struct Lanternfish {
+    count_days_until_spawn: Vec<usize>,
+}
+
+impl Lanternfish {
+    /// Returns the total number of fish, or None if there are too many
+    /// to count.
+    pub fn population(&self) -> Option<usize> {
+        self.count_days_until_spawn
+            .iter()
+            .try_fold(0usize, |sum, n| sum.checked_add(*n))
+    }
+
+    pub fn advance(&mut self) {
+        let spawned = self.count_days_until_spawn[0];
+        for i in 0..self.count_days_until_spawn.len() - 1 {
+            self.count_days_until_spawn[i] = self.count_days_until_spawn[i + 1]
+        }
+        self.count_days_until_spawn[6] += spawned;
+        self.count_days_until_spawn[8] = spawned;
+    }
+}
+
+impl FromStr for Lanternfish {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut count_days_until_spawn = vec![0; 9];
+        for word in s.split(',') {
+            match word.parse::<usize>() {
+                Ok(days_until_spawn) if days_until_spawn < count_days_until_spawn.len() => {
+                    count_days_until_spawn[days_until_spawn] += 1;
+                }
+                _ => return Err(ParseError::at_str(s, word, "days until spawn from 0 to 8")),
+            }
+        }
+        Ok(Lanternfish {
+            count_days_until_spawn,
+        })
+    }
+}
+
+impl Display for Lanternfish {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        for n in &self.count_days_until_spawn {
+            write!(f, "{}{}", sep, n)?;
+            sep = ",";
+        }
+        Ok(())
+    }
+}
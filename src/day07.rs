@@ -0,0 +1,90 @@
+//! Day 7: The Treachery of Whales.
+
+use crate::parse::parse_separated;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day7;
+
+impl Day for Day7 {
+    const DAY: u32 = 7;
+    const TITLE: &'static str = "The Treachery of Whales";
+    type Input = Vec<i64>;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(parse_separated::<i64>(input.trim_end(), ",")?)
+    }
+
+    fn part1(positions: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle7_1(positions)
+    }
+
+    fn part2(positions: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle7_2(positions)
+    }
+}
+
+fn puzzle7_1(positions: Vec<i64>) -> Result<Answer, PuzzleError> {
+    min_fuel(&positions, Some)
+}
+
+fn puzzle7_2(positions: Vec<i64>) -> Result<Answer, PuzzleError> {
+    min_fuel(&positions, |d| d.checked_mul(d + 1).map(|fuel| fuel / 2))
+}
+
+/// The furthest apart the crabs may be. Real inputs spread over about 2000
+/// positions, and every one of them is tried.
+const MAX_CRAB_SPREAD: i64 = 1 << 16;
+
+/// Finds the position the crabs can line up at for the least total fuel,
+/// where `fuel` returns what moving a crab a distance costs, or None if
+/// that's too much to count.
+fn min_fuel(positions: &[i64], fuel: impl Fn(i64) -> Option<i64>) -> Result<Answer, PuzzleError> {
+    let no_positions = || PuzzleError::invalid("no crab positions");
+    let min = *positions.iter().min().ok_or_else(no_positions)?;
+    let max = *positions.iter().max().ok_or_else(no_positions)?;
+    if max
+        .checked_sub(min)
+        .is_none_or(|spread| spread > MAX_CRAB_SPREAD)
+    {
+        return Err(PuzzleError::invalid(format!(
+            "crabs from {} to {} are too far apart",
+            min, max
+        )));
+    }
+    let total_fuel = |pos: i64| {
+        positions
+            .iter()
+            .try_fold(0i64, |total, p| total.checked_add(fuel((p - pos).abs())?))
+            .ok_or_else(|| PuzzleError::invalid(format!("fuel to reach {} is too much", pos)))
+    };
+    let mut best = (min, total_fuel(min)?);
+    for pos in min + 1..=max {
+        let total = total_fuel(pos)?;
+        if total < best.1 {
+            best = (pos, total);
+        }
+    }
+    let (min_pos, min_total_fuel) = best;
+
+    Ok(Answer::number(min_total_fuel).with("min_pos", min_pos))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleErrorKind;
+
+    #[test]
+    fn far_apart_crabs_are_rejected() {
+        for input in ["0,4000000000", "-9223372036854775808,9223372036854775807"] {
+            for part in [Day7::part1, Day7::part2] {
+                let err = part(Day7::parse(input).unwrap()).unwrap_err();
+                assert_eq!(err.kind(), PuzzleErrorKind::Invalid, "{}", err);
+            }
+        }
+        let answer = Day7::part2(Day7::parse("0,65536").unwrap()).unwrap();
+        assert_eq!(answer, Answer::number(1073774592).with("min_pos", 32768));
+    }
+}
@@ -0,0 +1,223 @@
+//! Day 8: Seven Segment Search.
+
+use std::fmt;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day8;
+
+impl Day for Day8 {
+    const DAY: u32 = 8;
+    const TITLE: &'static str = "Seven Segment Search";
+    type Input = Vec<Puzzle8Entry>;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(parse_puzzle8(input)?)
+    }
+
+    fn part1(entries: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle8_1(entries)
+    }
+
+    fn part2(entries: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle8_2(entries)
+    }
+}
+
+fn puzzle8_1(entries: Vec<Puzzle8Entry>) -> Result<Answer, PuzzleError> {
+    let easy_count = entries
+        .iter()
+        .flat_map(|e| e.output.iter())
+        .filter(|d| d.is_easy())
+        .count();
+    Ok(Answer::number(easy_count as i64))
+}
+
+fn puzzle8_2(entries: Vec<Puzzle8Entry>) -> Result<Answer, PuzzleError> {
+    let mut output_sum: u64 = 0;
+    for (i, e) in entries.iter().enumerate() {
+        output_sum += e
+            .decode()
+            .map_err(|msg| PuzzleError::invalid(msg).at_line(i + 1))?;
+    }
+    Ok(Answer::number(output_sum as i64))
+}
+
+fn parse_puzzle8(input: &str) -> Result<Vec<Puzzle8Entry>, ParseError> {
+    input
+        .trim()
+        .lines()
+        .map(|l| l.parse::<Puzzle8Entry>().map_err(|e| e.within(input, l)))
+        .collect()
+}
+
+pub struct Puzzle8Entry {
+    unique: Vec<Digit>,
+    output: Vec<Digit>,
+}
+
+impl Puzzle8Entry {
+    pub fn decode(&self) -> Result<u64, String> {
+        const UNKNOWN: u8 = u8::MAX;
+        let mut pattern_to_digit = [UNKNOWN; 128];
+        let mut digit_to_pattern = [0u8; 10];
+
+        // First pass: "1", "4", "7", "8".
+        let mut found = 0;
+        for pattern in self.unique.iter() {
+            let digit = match pattern.bits.count_ones() {
+                2 => 1u8,
+                4 => 4u8,
+                3 => 7u8,
+                7 => 8u8,
+                _ => continue,
+            };
+            pattern_to_digit[pattern.bits as usize] = digit;
+            digit_to_pattern[digit as usize] = pattern.bits;
+            found += 1;
+        }
+        if found != 4 {
+            return Err(String::from("expected one pattern each for 1, 4, 7, and 8"));
+        }
+
+        // Second pass: "2", "3", "5".
+        for pattern in self.unique.iter() {
+            if pattern.bits.count_ones() != 5 {
+                continue;
+            }
+            let common_with_one = (pattern.bits & digit_to_pattern[1]).count_ones();
+            let common_with_four_not_one =
+                (pattern.bits & digit_to_pattern[4] & !digit_to_pattern[1]).count_ones();
+            let digit = if common_with_one == 2 {
+                3u8
+            } else if common_with_four_not_one == 2 {
+                5u8
+            } else {
+                2u8
+            };
+            pattern_to_digit[pattern.bits as usize] = digit;
+            digit_to_pattern[digit as usize] = pattern.bits;
+            found += 1;
+        }
+        if found != 7 {
+            return Err(String::from("expected three patterns with 5 segments"));
+        }
+
+        // Third pass: "6", "9", "0".
+        for pattern in self.unique.iter() {
+            if pattern.bits.count_ones() != 6 {
+                continue;
+            }
+            let digit = if (pattern.bits & digit_to_pattern[4]).count_ones() == 4 {
+                9u8
+            } else if (pattern.bits & digit_to_pattern[1]).count_ones() == 2 {
+                0u8
+            } else {
+                6u8
+            };
+            pattern_to_digit[pattern.bits as usize] = digit;
+            digit_to_pattern[digit as usize] = pattern.bits;
+            found += 1;
+        }
+        if found != 10 {
+            return Err(String::from("expected three patterns with 6 segments"));
+        }
+
+        let mut output = 0;
+        for pattern in &self.output {
+            let digit = pattern_to_digit[pattern.bits as usize];
+            if digit == UNKNOWN {
+                return Err(format!(
+                    "output pattern {} is not a unique pattern",
+                    pattern
+                ));
+            }
+            output = output * 10 + digit as u64;
+        }
+        Ok(output)
+    }
+}
+
+impl FromStr for Puzzle8Entry {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_ascii_whitespace().collect();
+        match words.get(10) {
+            Some(&"|") => (),
+            Some(word) => return Err(ParseError::at_str(s, word, "'|' after 10 patterns")),
+            None if words.len() == 10 => return Err(ParseError::at(s, s.len(), "'|'")),
+            None => return Err(ParseError::at(s, s.len(), "unique pattern")),
+        }
+        if words.len() < 15 {
+            return Err(ParseError::at(s, s.len(), "output pattern"));
+        }
+        if words.len() > 15 {
+            return Err(ParseError::at_str(s, words[15], "end of line"));
+        }
+        let parse_digit = |w: &&str| w.parse::<Digit>().map_err(|e| e.within(s, w));
+        let unique: Result<Vec<Digit>, Self::Err> = words[0..10].iter().map(parse_digit).collect();
+        let output: Result<Vec<Digit>, Self::Err> = words[11..15].iter().map(parse_digit).collect();
+        Ok(Puzzle8Entry {
+            unique: unique?,
+            output: output?,
+        })
+    }
+}
+
+impl Display for Puzzle8Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        for d in &self.unique {
+            write!(f, "{}{}", sep, d)?;
+            sep = " ";
+        }
+        f.write_str(" |")?;
+        for d in &self.output {
+            write!(f, "{}{}", sep, d)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct Digit {
+    bits: u8,
+}
+
+impl Digit {
+    pub fn is_easy(&self) -> bool {
+        matches!(self.bits.count_ones(), 2 | 3 | 4 | 7)
+    }
+}
+
+impl FromStr for Digit {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut n = 0;
+        for (i, c) in s.char_indices() {
+            if !('a'..='g').contains(&c) {
+                return Err(ParseError::at(s, i, "segment letter a-g"));
+            }
+            n |= 1 << (c as u8 - b'a');
+        }
+        Ok(Digit { bits: n })
+    }
+}
+
+impl Display for Digit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = String::new();
+        for i in 0..8 {
+            if self.bits & (1 << i) != 0 {
+                s.push((b'a' + i) as char);
+            }
+        }
+        f.write_str(&s[..])
+    }
+}
@@ -0,0 +1,138 @@
+//! Day 9: Smoke Basin.
+
+use std::str::FromStr;
+
+use crate::grid::Connectivity;
+use crate::grid::Grid;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day9;
+
+impl Day for Day9 {
+    const DAY: u32 = 9;
+    const TITLE: &'static str = "Smoke Basin";
+    type Input = HeightMap;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.parse::<HeightMap>()?)
+    }
+
+    fn part1(hm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle9_1(hm)
+    }
+
+    fn part2(hm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle9_2(hm)
+    }
+}
+
+fn puzzle9_1(hm: HeightMap) -> Result<Answer, PuzzleError> {
+    let total_risk: u64 = hm.low_points().map(|(x, y)| 1 + hm.heights[(x, y)]).sum();
+    Ok(Answer::number(total_risk as i64))
+}
+
+fn puzzle9_2(hm: HeightMap) -> Result<Answer, PuzzleError> {
+    let basins = hm.basins()?;
+    let mut basin_sizes = Vec::<u64>::new();
+    for &bid in basins.iter() {
+        if bid == 0 {
+            continue;
+        }
+        let i = bid as usize;
+        if basin_sizes.len() <= i {
+            basin_sizes.resize(i + 1, 0);
+        }
+        basin_sizes[i] += 1;
+    }
+    basin_sizes.sort();
+    if basin_sizes.len() < 3 {
+        return Err(PuzzleError::no_solution("fewer than 3 basins"));
+    }
+    let product_of_largest: u64 = basin_sizes[basin_sizes.len() - 3..].iter().product();
+    Ok(Answer::number(product_of_largest as i64))
+}
+
+pub struct HeightMap {
+    heights: Grid<u64>,
+}
+
+impl HeightMap {
+    pub fn at(&self, x: usize, y: usize) -> u64 {
+        self.heights[(x, y)]
+    }
+
+    pub fn is_low_point(&self, x: usize, y: usize) -> bool {
+        let h = self.at(x, y);
+        self.heights
+            .neighbors(x, y, Connectivity::Orthogonal)
+            .all(|(nx, ny)| h < self.at(nx, ny))
+    }
+
+    pub fn low_points(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.heights
+            .positions()
+            .filter(|&(x, y)| self.is_low_point(x, y))
+    }
+
+    /// Returns the basin id of each point, or 0 for points that aren't in a
+    /// basin. Fails if some point is in more than one basin, because it
+    /// isn't a low point but has no lower neighbor to flow to.
+    pub fn basins(&self) -> Result<Grid<u64>, PuzzleError> {
+        let mut bs = Grid::new(self.heights.width(), self.heights.height(), 0);
+        let mut next_bid: u64 = 1;
+        fn find_basin(
+            hm: &HeightMap,
+            bs: &mut Grid<u64>,
+            next_bid: &mut u64,
+            x: usize,
+            y: usize,
+        ) -> Result<u64, PuzzleError> {
+            let i = (x, y);
+            if bs[i] != 0 {
+                // Basin already found for this point.
+                return Ok(bs[i]);
+            }
+            if hm.at(x, y) == 9 {
+                // High point, not in a basin.
+                return Ok(0);
+            }
+            if hm.is_low_point(x, y) {
+                // Bottom of new basin.
+                bs[i] = *next_bid;
+                *next_bid += 1;
+                return Ok(bs[i]);
+            }
+
+            // Recurse in lowest direction.
+            let h = hm.at(x, y);
+            let (flow_x, flow_y) = match hm
+                .heights
+                .neighbors(x, y, Connectivity::Orthogonal)
+                .min_by_key(|&(nx, ny)| hm.at(nx, ny))
+            {
+                Some((nx, ny)) if hm.at(nx, ny) < h => (nx, ny),
+                _ => return Err(PuzzleError::invalid("flat point").at(y + 1, x + 1)),
+            };
+            bs[i] = find_basin(hm, bs, next_bid, flow_x, flow_y)?;
+            Ok(bs[i])
+        }
+
+        for (x, y) in self.heights.positions() {
+            find_basin(self, &mut bs, &mut next_bid, x, y)?;
+        }
+        Ok(bs)
+    }
+}
+
+impl FromStr for HeightMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(HeightMap {
+            heights: s.parse()?,
+        })
+    }
+}
@@ -0,0 +1,130 @@
+//! Day 10: Syntax Scoring.
+
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day10;
+
+impl Day for Day10 {
+    const DAY: u32 = 10;
+    const TITLE: &'static str = "Syntax Scoring";
+    type Input = String;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(String::from(input))
+    }
+
+    fn part1(input: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle10_1(input.as_str())
+    }
+
+    fn part2(input: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle10_2(input.as_str())
+    }
+}
+
+fn puzzle10_1(input: &str) -> Result<Answer, PuzzleError> {
+    let err_score = |line: &str| {
+        if let Err(err) = parse_chunks(line) {
+            match line.chars().nth(err.column - 1) {
+                Some(')') => 3,
+                Some(']') => 57,
+                Some('}') => 1197,
+                Some('>') => 25137,
+                _ => 0,
+            }
+        } else {
+            0
+        }
+    };
+    let total_score: usize = input.trim().lines().map(err_score).sum();
+    Ok(Answer::number(total_score as i64))
+}
+
+fn puzzle10_2(input: &str) -> Result<Answer, PuzzleError> {
+    let char_score = |c| match c {
+        ')' => 1,
+        ']' => 2,
+        '}' => 3,
+        '>' => 4,
+        _ => 0,
+    };
+    // Lines are numbered from the start of the input, before it's trimmed.
+    let skipped_lines = input[..input.len() - input.trim_start().len()]
+        .matches('\n')
+        .count();
+    let mut scores = Vec::<i64>::new();
+    for (i, line) in input.trim().lines().enumerate() {
+        if let Ok(cs) = parse_chunks(line) {
+            let score = cs
+                .iter()
+                .try_fold(0i64, |total, &c| {
+                    total.checked_mul(5)?.checked_add(char_score(c))
+                })
+                .ok_or_else(|| {
+                    PuzzleError::invalid("completion score is too big")
+                        .at_line(skipped_lines + i + 1)
+                })?;
+            scores.push(score);
+        }
+    }
+    scores.sort();
+    if scores.is_empty() {
+        return Err(PuzzleError::no_solution("no incomplete lines"));
+    }
+    let median_score = scores[scores.len() / 2];
+    Ok(Answer::number(median_score))
+}
+
+/// Parses a line of chunks, returning the closing characters needed to
+/// complete it. Corrupted lines are errors.
+pub fn parse_chunks(s: &str) -> Result<Vec<char>, ParseError> {
+    let mut stack = Vec::<char>::new();
+    for (i, c) in s.char_indices() {
+        let open = match c {
+            '<' => Some('>'),
+            '(' => Some(')'),
+            '[' => Some(']'),
+            '{' => Some('}'),
+            _ => None,
+        };
+        if let Some(want) = open {
+            stack.push(want);
+            continue;
+        }
+        match stack.pop() {
+            Some(want) if c == want => (),
+            Some(want) => {
+                return Err(ParseError::at(
+                    s,
+                    i,
+                    format!("'{}' or an opening character", want),
+                ))
+            }
+            None => return Err(ParseError::at(s, i, "opening character")),
+        }
+    }
+    stack.reverse();
+    Ok(stack)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleErrorKind;
+
+    #[test]
+    fn huge_completion_score_is_rejected() {
+        // 28 closing characters score more than an i64 can hold.
+        let input = format!("\n<>\n{}\n", "(".repeat(28));
+        let err = Day10::part2(Day10::parse(&input).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), PuzzleErrorKind::Invalid, "{}", err);
+        assert_eq!(err.line(), Some(3));
+
+        let input = "(".repeat(27);
+        let answer = Day10::part2(Day10::parse(&input).unwrap()).unwrap();
+        assert_eq!(answer, Answer::number((5i64.pow(27) - 1) / 4));
+    }
+}
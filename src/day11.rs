@@ -0,0 +1,159 @@
+//! Day 11: Dumbo Octopus.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::mem;
+use std::str::FromStr;
+
+use crate::grid::Connectivity;
+use crate::grid::Grid;
+use crate::grid::Neighbors;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day11;
+
+impl Day for Day11 {
+    const DAY: u32 = 11;
+    const TITLE: &'static str = "Dumbo Octopus";
+    type Input = OctopusMap;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.trim_end().parse::<OctopusMap>()?)
+    }
+
+    fn part1(om: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle11_1(om)
+    }
+
+    fn part2(om: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle11_2(om)
+    }
+}
+
+fn puzzle11_1(mut om: OctopusMap) -> Result<Answer, PuzzleError> {
+    let n = 100;
+    for _ in 0..n {
+        om.step();
+    }
+    Ok(Answer::number(om.flash_count as i64).with("steps", n))
+}
+
+fn puzzle11_2(mut om: OctopusMap) -> Result<Answer, PuzzleError> {
+    let mut n = 0;
+    while !om.all_flashed() {
+        om.step();
+        n += 1;
+    }
+    Ok(Answer::number(n))
+}
+
+pub struct OctopusMap {
+    energies: Grid<u64>,
+
+    /// How many flashes there have been in all the steps so far.
+    pub flash_count: u64,
+
+    /// Whether flashes spread across the edges of the map to the opposite
+    /// side, as if the map were wrapped around a torus.
+    pub wrap: bool,
+}
+
+impl OctopusMap {
+    fn neighbors(&self, x: usize, y: usize) -> Neighbors {
+        if self.wrap {
+            self.energies.wrapping_neighbors(x, y, Connectivity::All)
+        } else {
+            self.energies.neighbors(x, y, Connectivity::All)
+        }
+    }
+
+    pub fn step(&mut self) {
+        // Increase energy.
+        let mut flashers = Vec::<(usize, usize)>::new();
+        for (x, y) in self.energies.positions() {
+            let e = &mut self.energies[(x, y)];
+            *e += 1;
+            if *e == 10 {
+                flashers.push((x, y));
+            }
+        }
+
+        // Flashers propagate energy to neighbors, maybe making them flash.
+        // Stop when there are no new flashers.
+        let mut prev_flashers = Vec::<(usize, usize)>::new();
+        while !flashers.is_empty() {
+            self.flash_count += flashers.len() as u64;
+            mem::swap(&mut flashers, &mut prev_flashers);
+            for &(x, y) in &prev_flashers {
+                for n in self.neighbors(x, y) {
+                    let e = &mut self.energies[n];
+                    if *e == 10 {
+                        continue;
+                    }
+                    *e += 1;
+                    if *e == 10 {
+                        flashers.push(n)
+                    }
+                }
+            }
+            prev_flashers.resize(0, (0, 0));
+        }
+
+        // Reset flashers' energy to zero.
+        for e in self.energies.iter_mut() {
+            if *e == 10 {
+                *e = 0;
+            }
+        }
+    }
+
+    pub fn all_flashed(&self) -> bool {
+        self.energies.iter().all(|&e| e == 0)
+    }
+}
+
+impl FromStr for OctopusMap {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(OctopusMap {
+            energies: s.parse()?,
+            flash_count: 0,
+            wrap: false,
+        })
+    }
+}
+
+impl Display for OctopusMap {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.energies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flashes_wrap_across_edges() {
+        let mut om: OctopusMap = "9000\n0000\n0000\n0000".parse().unwrap();
+        let mut wrapped: OctopusMap = om.to_string().parse().unwrap();
+        wrapped.wrap = true;
+        om.step();
+        wrapped.step();
+        assert_eq!(om.to_string(), "0211\n2211\n1111\n1111");
+        assert_eq!(wrapped.to_string(), "0212\n2212\n1111\n2212");
+        assert_eq!((om.flash_count, wrapped.flash_count), (1, 1));
+    }
+
+    #[test]
+    fn rectangular_map() {
+        let mut om: OctopusMap = "90\n00\n00".parse().unwrap();
+        om.step();
+        assert_eq!(om.to_string(), "02\n22\n11");
+    }
+}
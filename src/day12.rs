@@ -0,0 +1,177 @@
+//! Day 12: Passage Pathing.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day12;
+
+impl Day for Day12 {
+    const DAY: u32 = 12;
+    const TITLE: &'static str = "Passage Pathing";
+    type Input = CaveMap;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.parse::<CaveMap>()?)
+    }
+
+    fn part1(cm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle12_1(cm)
+    }
+
+    fn part2(cm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle12_2(cm)
+    }
+}
+
+fn puzzle12_1(cm: CaveMap) -> Result<Answer, PuzzleError> {
+    let path_count = cm
+        .count_paths("start", "end", false)
+        .map_err(PuzzleError::invalid)?;
+    Ok(Answer::number(path_count as i64))
+}
+
+fn puzzle12_2(cm: CaveMap) -> Result<Answer, PuzzleError> {
+    let path_count = cm
+        .count_paths("start", "end", true)
+        .map_err(PuzzleError::invalid)?;
+    Ok(Answer::number(path_count as i64))
+}
+
+#[derive(Default)]
+pub struct CaveMap {
+    name_to_index: HashMap<String, usize>,
+    caves: Vec<Cave>,
+}
+
+impl CaveMap {
+    pub fn new() -> CaveMap {
+        CaveMap {
+            name_to_index: HashMap::<String, usize>::new(),
+            caves: Vec::<Cave>::new(),
+        }
+    }
+
+    fn ensure_cave(&mut self, name: &str) -> usize {
+        match self.name_to_index.get(name) {
+            Some(ix) => *ix,
+            None => {
+                let ix = self.caves.len();
+                let is_small = name.starts_with(|c: char| c.is_lowercase());
+                self.name_to_index.insert(String::from(name), ix);
+                self.caves.push(Cave {
+                    is_small,
+                    neighbors: Vec::<usize>::new(),
+                });
+                ix
+            }
+        }
+    }
+
+    pub fn ensure_passage(&mut self, from: &str, to: &str) {
+        let from_ix = self.ensure_cave(from);
+        let to_ix = self.ensure_cave(to);
+        if self.caves[from_ix].neighbors.contains(&to_ix) {
+            return;
+        }
+        self.caves[from_ix].neighbors.push(to_ix);
+        self.caves[to_ix].neighbors.push(from_ix);
+    }
+
+    /// Counts distinct paths from one cave to another that visit small caves
+    /// at most once, or if can_visit_twice is set, that visit a single small
+    /// cave (other than the endpoints) at most twice. Fails if either cave
+    /// is missing, or if two big caves are connected, since then there are
+    /// infinitely many paths.
+    pub fn count_paths(
+        &self,
+        from: &str,
+        to: &str,
+        can_visit_twice: bool,
+    ) -> Result<usize, String> {
+        let from_index = *self
+            .name_to_index
+            .get(from)
+            .ok_or_else(|| format!("cave '{}' not found", from))?;
+        let to_index = *self
+            .name_to_index
+            .get(to)
+            .ok_or_else(|| format!("cave '{}' not found", to))?;
+        for (name, &i) in &self.name_to_index {
+            let cave = &self.caves[i];
+            if !cave.is_small && cave.neighbors.iter().any(|&n| !self.caves[n].is_small) {
+                return Err(format!(
+                    "big cave '{}' is connected to another big cave",
+                    name
+                ));
+            }
+        }
+        let mut path = CavePath { caves: vec![] };
+        let mut paths = HashSet::<CavePath>::new();
+
+        fn visit(
+            cm: &CaveMap,
+            i: usize,
+            start: usize,
+            end: usize,
+            can_visit_twice: bool,
+            path: &mut CavePath,
+            paths: &mut HashSet<CavePath>,
+        ) {
+            path.caves.push(i);
+            if i == end {
+                paths.insert(path.clone());
+            } else {
+                for &next in &cm.caves[i].neighbors {
+                    if !cm.caves[next].is_small || !path.caves.contains(&next) {
+                        visit(cm, next, start, end, can_visit_twice, path, paths);
+                    } else if can_visit_twice && next != start && next != end {
+                        visit(cm, next, start, end, false, path, paths);
+                    }
+                }
+            }
+            path.caves.pop();
+        }
+
+        visit(
+            self,
+            from_index,
+            from_index,
+            to_index,
+            can_visit_twice,
+            &mut path,
+            &mut paths,
+        );
+        Ok(paths.len())
+    }
+}
+
+impl FromStr for CaveMap {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cm = CaveMap::new();
+        for line in s.lines() {
+            let (from, to) = match line.split_once('-') {
+                Some((from, to)) if !from.is_empty() && !to.is_empty() => (from, to),
+                _ => return Err(ParseError::at_str(s, line, "passage like 'start-A'")),
+            };
+            cm.ensure_passage(from, to);
+        }
+        Ok(cm)
+    }
+}
+
+struct Cave {
+    is_small: bool,
+    neighbors: Vec<usize>,
+}
+
+#[derive(Clone, Eq, PartialEq, Hash)]
+struct CavePath {
+    caves: Vec<usize>,
+}
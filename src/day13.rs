@@ -0,0 +1,219 @@
+//! Day 13: Transparent Origami.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::from_utf8;
+use std::str::FromStr;
+
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day13;
+
+impl Day for Day13 {
+    const DAY: u32 = 13;
+    const TITLE: &'static str = "Transparent Origami";
+    type Input = TransparentPaper;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.trim_end().parse::<TransparentPaper>()?)
+    }
+
+    fn part1(paper: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle13_1(paper)
+    }
+
+    fn part2(paper: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle13_2(paper)
+    }
+}
+
+fn puzzle13_1(mut paper: TransparentPaper) -> Result<Answer, PuzzleError> {
+    paper = paper.fold_n(1).map_err(PuzzleError::invalid)?;
+    Ok(Answer::number(paper.dots.len() as i64))
+}
+
+fn puzzle13_2(mut paper: TransparentPaper) -> Result<Answer, PuzzleError> {
+    paper = paper
+        .fold_n(paper.folds.len())
+        .map_err(PuzzleError::invalid)?;
+    let (width, height) = paper.size();
+    let cells = width.checked_add(1).and_then(|row| row.checked_mul(height));
+    if cells.is_none_or(|cells| cells > MAX_PAPER_CELLS) {
+        return Err(PuzzleError::invalid(format!(
+            "folded paper of {} by {} is too big to draw",
+            width, height
+        )));
+    }
+    Ok(Answer::text(paper.to_string()))
+}
+
+/// The most characters the folded paper may be drawn with. Real inputs need
+/// a few hundred.
+const MAX_PAPER_CELLS: usize = 1 << 24;
+
+pub struct TransparentPaper {
+    dots: Vec<(usize, usize)>,
+    folds: Vec<TransparentPaperFold>,
+}
+
+impl TransparentPaper {
+    /// Applies the first n folds, returning the folded paper with the
+    /// remaining folds.
+    pub fn fold_n(&self, n: usize) -> Result<TransparentPaper, String> {
+        if n > self.folds.len() {
+            return Err(format!("only {} folds, can't fold {}", self.folds.len(), n));
+        }
+        let mut transformed_dots = Vec::<(usize, usize)>::new();
+        for dot in &self.dots {
+            let mut transformed_dot = *dot;
+            for i in 0..n {
+                let fold = self.folds[i];
+                transformed_dot = fold.transform(transformed_dot).ok_or_else(|| {
+                    format!(
+                        "fold {} moves dot {},{} past the edge of the paper",
+                        i + 1,
+                        dot.0,
+                        dot.1
+                    )
+                })?;
+            }
+            transformed_dots.push(transformed_dot);
+        }
+
+        transformed_dots.sort_by(|(x1, y1), (x2, y2)| match y1.cmp(y2) {
+            Ordering::Equal => x1.cmp(x2),
+            c => c,
+        });
+        transformed_dots.dedup();
+
+        let mut remaining_folds = Vec::<TransparentPaperFold>::new();
+        remaining_folds.extend_from_slice(&self.folds[n..]);
+
+        Ok(TransparentPaper {
+            dots: transformed_dots,
+            folds: remaining_folds,
+        })
+    }
+
+    /// Returns the width and height of the smallest sheet that holds every
+    /// dot. They saturate at usize::MAX rather than overflowing.
+    pub fn size(&self) -> (usize, usize) {
+        let size_x = self.dots.iter().fold(0, |s, (x, _)| s.max(*x));
+        let size_y = self.dots.iter().fold(0, |s, (_, y)| s.max(*y));
+        (size_x.saturating_add(1), size_y.saturating_add(1))
+    }
+}
+
+impl fmt::Display for TransparentPaper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (size_x, size_y) = self.size();
+        let mut buf = Vec::<u8>::with_capacity((size_x + 1) * size_y);
+        for _ in 0..size_y {
+            buf.resize(buf.len() + size_x, b'.');
+            buf.push(b'\n');
+        }
+        for (x, y) in &self.dots {
+            buf[y * (size_x + 1) + x] = b'#';
+        }
+        f.write_str(from_utf8(&buf[..]).unwrap())
+    }
+}
+
+#[derive(Clone, Copy)]
+enum TransparentPaperFold {
+    X(usize),
+    Y(usize),
+}
+
+impl TransparentPaperFold {
+    /// Returns where a dot ends up after folding, or None if it would be
+    /// folded past the left or top edge of the paper.
+    fn transform(&self, (x, y): (usize, usize)) -> Option<(usize, usize)> {
+        match self {
+            TransparentPaperFold::X(axis) if x > *axis => Some((axis.checked_sub(x - axis)?, y)),
+            TransparentPaperFold::Y(axis) if y > *axis => Some((x, axis.checked_sub(y - axis)?)),
+            _ => Some((x, y)),
+        }
+    }
+}
+
+impl FromStr for TransparentPaper {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse_int = |word: &str| {
+            word.parse::<usize>()
+                .map_err(|_| ParseError::at_str(s, word, "integer"))
+        };
+
+        let parse_dot = |line: &str| -> Result<(usize, usize), Self::Err> {
+            match line.split_once(',') {
+                Some((x, y)) => Ok((parse_int(x)?, parse_int(y)?)),
+                None => Err(ParseError::at(s, offset_in(s, line) + line.len(), "','")),
+            }
+        };
+
+        let parse_fold = |line: &str| -> Result<TransparentPaperFold, Self::Err> {
+            if let Some(axis) = line.strip_prefix("fold along x=") {
+                Ok(TransparentPaperFold::X(parse_int(axis)?))
+            } else if let Some(axis) = line.strip_prefix("fold along y=") {
+                Ok(TransparentPaperFold::Y(parse_int(axis)?))
+            } else {
+                Err(ParseError::at_str(
+                    s,
+                    line,
+                    "'fold along x=' or 'fold along y='",
+                ))
+            }
+        };
+
+        let mut dots = Vec::<(usize, usize)>::new();
+        let mut folds = Vec::<TransparentPaperFold>::new();
+        let mut line_iter = s.trim().lines();
+        loop {
+            match line_iter.next() {
+                Some(line) if !line.is_empty() => {
+                    dots.push(parse_dot(line)?);
+                }
+                _ => break,
+            }
+        }
+
+        loop {
+            match line_iter.next() {
+                Some(line) if !line.is_empty() => {
+                    folds.push(parse_fold(line)?);
+                }
+                _ => break,
+            }
+        }
+        Ok(TransparentPaper { dots, folds })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PuzzleErrorKind;
+
+    #[test]
+    fn huge_paper_is_rejected() {
+        let input = "4000000000,4000000000\n\nfold along x=4000000001\n";
+        let err = Day13::part2(Day13::parse(input).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), PuzzleErrorKind::Invalid, "{}", err);
+
+        let input = "18446744073709551615,0\n\nfold along y=1\n";
+        let err = Day13::part2(Day13::parse(input).unwrap()).unwrap_err();
+        assert_eq!(err.kind(), PuzzleErrorKind::Invalid, "{}", err);
+    }
+
+    #[test]
+    fn folds_near_the_largest_axis() {
+        let input = "18446744073709551615,1\n\nfold along x=18446744073709551614\n";
+        let paper = Day13::parse(input).unwrap().fold_n(1).unwrap();
+        assert_eq!(paper.dots, [(18446744073709551613, 1)]);
+    }
+}
@@ -0,0 +1,183 @@
+//! Day 14: Extended Polymerization.
+
+use std::collections::HashMap;
+use std::mem;
+use std::str::FromStr;
+
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day14;
+
+impl Day for Day14 {
+    const DAY: u32 = 14;
+    const TITLE: &'static str = "Extended Polymerization";
+    type Input = Polymer;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.trim_end().parse::<Polymer>()?)
+    }
+
+    fn part1(p: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle14_1(p)
+    }
+
+    fn part2(p: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle14_2(p)
+    }
+}
+
+fn puzzle14_1(mut p: Polymer) -> Result<Answer, PuzzleError> {
+    let n = 10;
+    p.step(n);
+    let mut hist = HashMap::<u8, usize>::new();
+    p.poly.iter().for_each(|e| {
+        match hist.get_mut(e) {
+            Some(n) => *n += 1,
+            None => {
+                hist.insert(*e, 1);
+            }
+        };
+    });
+    let most_common = hist.values().max().unwrap();
+    let least_common = hist.values().min().unwrap();
+    let diff = most_common - least_common;
+
+    Ok(Answer::number(diff as i64)
+        .with("steps", n as i64)
+        .with("length", p.poly.len() as i64))
+}
+
+fn puzzle14_2(p: Polymer) -> Result<Answer, PuzzleError> {
+    let n = 40;
+    let hist = p.hist(n);
+    let most_common = hist.iter().max().unwrap();
+    let least_common = hist.iter().filter(|n| *n > &0).min().unwrap();
+    let diff = most_common - least_common;
+
+    Ok(Answer::number(diff as i64).with("steps", n as i64))
+}
+
+pub struct Polymer {
+    poly: Vec<u8>,
+    rules: Vec<u8>,
+}
+
+impl Polymer {
+    pub fn step(&mut self, n: usize) {
+        let mut next = Vec::<u8>::new();
+        for _ in 0..n {
+            next.clear();
+            next.push(self.poly[0]);
+            for i in 0..self.poly.len() - 1 {
+                let (l, r) = (self.poly[i], self.poly[i + 1]);
+                let ix = Self::rule_index(l, r);
+                let b = self.rules[ix];
+                if b != 0 {
+                    next.push(b);
+                }
+                next.push(r);
+            }
+            mem::swap(&mut self.poly, &mut next);
+        }
+    }
+
+    pub fn hist(&self, n: usize) -> [usize; 26] {
+        fn pair_hist(
+            l: u8,
+            r: u8,
+            n: usize,
+            rules: &[u8],
+            mem: &mut HashMap<(u8, u8, usize), [usize; 26]>,
+        ) -> [usize; 26] {
+            let key = (l, r, n);
+            if let Some(&hist) = mem.get(&key) {
+                return hist;
+            }
+            let b = rules[Polymer::rule_index(l, r)];
+            if n == 0 || b == 0 {
+                let hist = [0; 26];
+                mem.insert(key, hist);
+                return hist;
+            }
+            let lhist = pair_hist(l, b, n - 1, rules, mem);
+            let rhist = pair_hist(b, r, n - 1, rules, mem);
+            let mut hist = [0; 26];
+            for i in 0..26 {
+                hist[i] = lhist[i] + rhist[i];
+            }
+            hist[(b - b'A') as usize] += 1;
+            mem.insert(key, hist);
+            hist
+        }
+
+        let mut mem = HashMap::<(u8, u8, usize), [usize; 26]>::new();
+        let mut hist = [0usize; 26];
+        for i in 0..self.poly.len() - 1 {
+            let (l, r) = (self.poly[i], self.poly[i + 1]);
+            hist[(l - b'A') as usize] += 1;
+            let phist = pair_hist(l, r, n, &self.rules[..], &mut mem);
+            for j in 0..26 {
+                hist[j] += phist[j];
+            }
+        }
+        hist[(self.poly[self.poly.len() - 1] - b'A') as usize] += 1;
+        hist
+    }
+
+    fn rule_index(l: u8, r: u8) -> usize {
+        ((l - b'A') as usize) * 26 + (r - b'A') as usize
+    }
+}
+
+impl FromStr for Polymer {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Returns an error at the first character of `part` that isn't an
+        // element.
+        let check_elements = |part: &str| match part.find(|c: char| !c.is_ascii_uppercase()) {
+            Some(i) => Err(ParseError::at(
+                s,
+                offset_in(s, part) + i,
+                "element letter A-Z",
+            )),
+            None => Ok(()),
+        };
+
+        let parse_rule = |line: &str| {
+            match line.find(" -> ") {
+                Some(2) if line.len() == 7 => (),
+                _ => return Err(ParseError::at_str(s, line, "rule like 'AB -> C'")),
+            };
+            check_elements(&line[..2])?;
+            check_elements(&line[6..])?;
+            let bytes = line.as_bytes();
+            Ok((bytes[0], bytes[1], bytes[6]))
+        };
+
+        let mut line_iter = s.lines();
+        let tpl = match line_iter.next() {
+            Some(tpl_str) if !tpl_str.is_empty() => {
+                check_elements(tpl_str)?;
+                let mut tpl = Vec::<u8>::new();
+                tpl.extend_from_slice(tpl_str.as_bytes());
+                tpl
+            }
+            _ => return Err(ParseError::at(s, 0, "polymer template")),
+        };
+        match line_iter.next() {
+            Some("") => (),
+            Some(line) => return Err(ParseError::at_str(s, line, "blank line after template")),
+            None => return Err(ParseError::at(s, s.len(), "blank line after template")),
+        };
+        let mut rules = vec![0; 26 * 26];
+        for line in line_iter {
+            let (l, r, b) = parse_rule(line)?;
+            rules[Self::rule_index(l, r)] = b;
+        }
+        Ok(Polymer { poly: tpl, rules })
+    }
+}
@@ -0,0 +1,457 @@
+//! Day 15: Chiton.
+
+use std::str::FromStr;
+
+use crate::grid::Connectivity;
+use crate::grid::Grid;
+use crate::grid::Neighbors;
+use crate::parse::ParseError;
+use crate::pathfinding;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+use crate::RenderOptions;
+use crate::Rendering;
+
+pub struct Day15;
+
+impl Day for Day15 {
+    const DAY: u32 = 15;
+    const TITLE: &'static str = "Chiton";
+    type Input = ChitonMap;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(input.trim_end().parse::<ChitonMap>()?)
+    }
+
+    fn part1(cm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle15_1(cm)
+    }
+
+    fn part2(cm: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle15_2(cm)
+    }
+
+    fn render(
+        part: u32,
+        cm: &Self::Input,
+        options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError> {
+        let map = match part {
+            1 => cm.tiled(1),
+            _ => cm.tiled(5),
+        };
+        let path = map
+            .lowest_risk_path()
+            .ok_or_else(|| PuzzleError::no_solution("empty map"))?;
+        Ok(Some(map.render_path(&path.nodes, options.color)))
+    }
+}
+
+fn puzzle15_1(cm: ChitonMap) -> Result<Answer, PuzzleError> {
+    let path = cm
+        .tiled(1)
+        .lowest_risk_path()
+        .ok_or_else(|| PuzzleError::no_solution("empty map"))?;
+    Ok(Answer::number(path.cost as i64))
+}
+
+fn puzzle15_2(cm: ChitonMap) -> Result<Answer, PuzzleError> {
+    let path = cm
+        .tiled(5)
+        .lowest_risk_path()
+        .ok_or_else(|| PuzzleError::no_solution("empty map"))?;
+    Ok(Answer::number(path.cost as i64))
+}
+
+pub struct ChitonMap {
+    risks: Grid<usize>,
+}
+
+impl ChitonMap {
+    /// Returns a view of the map repeated `factor` times across and down,
+    /// without copying it.
+    pub fn tiled(&self, factor: usize) -> TiledChitonMap<'_> {
+        TiledChitonMap { map: self, factor }
+    }
+}
+
+/// A chiton map repeated `factor` times across and down. Each repetition
+/// to the right or down adds 1 to every risk, wrapping from 9 back to 1.
+/// Risks are worked out as they're needed, so large tilings cost nothing
+/// until they're searched.
+#[derive(Clone, Copy)]
+pub struct TiledChitonMap<'a> {
+    map: &'a ChitonMap,
+    factor: usize,
+}
+
+impl<'a> TiledChitonMap<'a> {
+    pub fn width(&self) -> usize {
+        self.map.risks.width() * self.factor
+    }
+
+    pub fn height(&self) -> usize {
+        self.map.risks.height() * self.factor
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width() && y < self.height()
+    }
+
+    pub fn risk(&self, (x, y): (usize, usize)) -> usize {
+        let (width, height) = (self.map.risks.width(), self.map.risks.height());
+        wrap_risk(self.map.risks[(x % width, y % height)] + x / width + y / height)
+    }
+
+    /// Returns the lowest risk of any position, or 0 if the map is empty.
+    /// Each risk takes at most 9 different values across the tiles, so
+    /// only the first 9 tiles down a diagonal need checking.
+    pub fn min_risk(&self) -> usize {
+        if self.factor == 0 {
+            return 0;
+        }
+        let shifts = 2 * (self.factor - 1);
+        self.map
+            .risks
+            .iter()
+            .flat_map(|&risk| (0..=shifts.min(8)).map(move |shift| wrap_risk(risk + shift)))
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// Returns the positions next to `pos`, each with the risk of entering it.
+    pub fn moves(
+        &self,
+        (x, y): (usize, usize),
+    ) -> impl Iterator<Item = ((usize, usize), usize)> + 'a {
+        let map = *self;
+        Neighbors::new(
+            self.width(),
+            self.height(),
+            x,
+            y,
+            Connectivity::Orthogonal,
+            false,
+        )
+        .map(move |next| (next, map.risk(next)))
+    }
+
+    /// Returns a path from the top left to the bottom right with the lowest
+    /// total risk, or None if the map is empty. The starting position's risk
+    /// doesn't count, since it's never entered.
+    pub fn lowest_risk_path(&self) -> Option<pathfinding::Path<(usize, usize)>> {
+        if self.map.risks.is_empty() || self.factor == 0 {
+            return None;
+        }
+        let to = (self.width() - 1, self.height() - 1);
+        // Corner to corner, most of the map gets searched, so a slot for
+        // every position is cheaper than hashing them.
+        let goals = [to];
+        let heuristic = self.heuristic(&goals);
+        pathfinding::grid_a_star(
+            self.width(),
+            self.height(),
+            [(0, 0)],
+            |&pos| pos == to,
+            |&pos| self.moves(pos),
+            heuristic,
+        )
+    }
+
+    /// Returns a path from `from` to `to` with the lowest total risk, or None
+    /// if either is outside the map. The risk of `from` doesn't count. Only
+    /// the positions searched take up memory, so nearby positions are quick
+    /// to search between even on huge tilings.
+    pub fn lowest_risk_path_between(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Option<pathfinding::Path<(usize, usize)>> {
+        if !self.contains(from) || !self.contains(to) {
+            return None;
+        }
+        let goals = [to];
+        let heuristic = self.heuristic(&goals);
+        pathfinding::a_star([from], |&pos| pos == to, |&pos| self.moves(pos), heuristic)
+    }
+
+    /// Returns an A* heuristic for getting to the nearest of `goals`. Every
+    /// move costs at least the lowest risk, which may be 0, so the Manhattan
+    /// distance is scaled by it to never overestimate.
+    fn heuristic<'g>(&self, goals: &'g [(usize, usize)]) -> impl Fn(&(usize, usize)) -> usize + 'g {
+        let distance = pathfinding::manhattan_heuristic(goals);
+        let min_risk = self.min_risk();
+        move |pos| distance(pos) * min_risk
+    }
+
+    /// Draws the map with a path picked out. In the text, every position
+    /// shows its risk, and the path is highlighted with terminal escape codes
+    /// if `color` is set, or drawn as '*' if not. In the image, risk is
+    /// shaded from light gray for 1 to black for 9, and the path is red.
+    pub fn render_path(&self, path: &[(usize, usize)], color: bool) -> Rendering {
+        let (width, height) = (self.width(), self.height());
+        let mut on_path = Grid::new(width, height, false);
+        for &pos in path {
+            on_path[pos] = true;
+        }
+        let cells = || {
+            on_path
+                .positions()
+                .map(|pos| (self.risk(pos), on_path[pos]))
+        };
+        let text = Grid::from_cells(
+            width,
+            height,
+            cells()
+                .map(|(risk, on_path)| match (on_path, color) {
+                    (true, true) => format!("\x1b[7m{}\x1b[0m", risk),
+                    (true, false) => String::from("*"),
+                    (false, _) => risk.to_string(),
+                })
+                .collect(),
+        );
+        let image = Grid::from_cells(
+            width,
+            height,
+            cells()
+                .map(|(risk, on_path)| match on_path {
+                    true => [255, 0, 0],
+                    false => {
+                        let shade = (255 - risk * 255 / 9) as u8;
+                        [shade, shade, shade]
+                    }
+                })
+                .collect(),
+        );
+        Rendering {
+            text: text.to_string(),
+            image,
+            svg: None,
+        }
+    }
+
+    /// Returns the lowest total risk of getting from `from` to `to`, or None
+    /// if either is outside the map.
+    pub fn lowest_risk(&self, from: (usize, usize), to: (usize, usize)) -> Option<usize> {
+        self.lowest_risk_path_between(from, to)
+            .map(|path| path.cost)
+    }
+
+    /// Returns the lowest total risk of getting to each position from the
+    /// nearest of `sources`. Sources outside the map are ignored, and if none
+    /// are left every position is usize::MAX.
+    pub fn risk_field(&self, sources: &[(usize, usize)]) -> Grid<usize> {
+        let (width, height) = (self.width(), self.height());
+        let starts = sources.iter().copied().filter(|&pos| self.contains(pos));
+        let risks = pathfinding::grid_distances(width, height, starts, |&pos| self.moves(pos));
+        Grid::from_cells(width, height, risks)
+    }
+
+    /// Returns up to `k` distinct paths from `from` to `to` that don't visit
+    /// any position twice, lowest total risk first.
+    pub fn lowest_risk_paths(
+        &self,
+        from: (usize, usize),
+        to: (usize, usize),
+        k: usize,
+    ) -> Vec<pathfinding::Path<(usize, usize)>> {
+        if !self.contains(from) || !self.contains(to) {
+            return Vec::new();
+        }
+        pathfinding::k_cheapest_paths(from, to, k, |&pos| self.moves(pos))
+    }
+}
+
+/// Wraps a tiled risk above 9 back around to 1.
+fn wrap_risk(risk: usize) -> usize {
+    match risk {
+        0..=9 => risk,
+        _ => (risk - 1) % 9 + 1,
+    }
+}
+
+impl FromStr for ChitonMap {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ChitonMap { risks: s.parse()? })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rectangular_map() {
+        let cm: ChitonMap = "19\n11\n91".parse().unwrap();
+        let map = cm.tiled(1);
+        assert_eq!((map.width(), map.height()), (2, 3));
+        let path = map.lowest_risk_path().unwrap();
+        assert_eq!(path.nodes, [(0, 0), (0, 1), (1, 1), (1, 2)]);
+        assert_eq!(path.cost, 3);
+
+        let tiled = cm.tiled(2);
+        assert_eq!((tiled.width(), tiled.height()), (4, 6));
+        assert_eq!(tiled.risk((3, 5)), 3);
+    }
+
+    #[test]
+    fn render_path_shows_every_risk() {
+        let cm: ChitonMap = "19\n11\n91".parse().unwrap();
+        let map = cm.tiled(1);
+        let path = map.lowest_risk_path().unwrap();
+        assert_eq!(map.render_path(&path.nodes, false).text, "*9\n**\n9*");
+        let highlighted = map.render_path(&path.nodes, true);
+        assert_eq!(highlighted.text.lines().nth(2), Some("9\x1b[7m1\x1b[0m"));
+        assert_eq!(highlighted.image[(1, 0)], [0, 0, 0]);
+        assert_eq!(highlighted.image[(1, 2)], [255, 0, 0]);
+    }
+
+    /// Returns the total risk of every path from `from` to `to` that doesn't
+    /// visit any position twice, by trying them all.
+    fn simple_path_risks(
+        map: &TiledChitonMap,
+        from: (usize, usize),
+        to: (usize, usize),
+    ) -> Vec<usize> {
+        fn visit(
+            map: &TiledChitonMap,
+            path: &mut Vec<(usize, usize)>,
+            risk: usize,
+            to: (usize, usize),
+            risks: &mut Vec<usize>,
+        ) {
+            let pos = path[path.len() - 1];
+            if pos == to {
+                risks.push(risk);
+                return;
+            }
+            for (next, next_risk) in map.moves(pos) {
+                if !path.contains(&next) {
+                    path.push(next);
+                    visit(map, path, risk + next_risk, to, risks);
+                    path.pop();
+                }
+            }
+        }
+        let mut risks = Vec::new();
+        visit(map, &mut vec![from], 0, to, &mut risks);
+        risks.sort_unstable();
+        risks
+    }
+
+    #[test]
+    fn lowest_risk_paths_agree_with_every_simple_path() {
+        let cm: ChitonMap = "163\n138\n213".parse().unwrap();
+        let map = cm.tiled(1);
+        let positions: Vec<(usize, usize)> =
+            (0..3).flat_map(|y| (0..3).map(move |x| (x, y))).collect();
+        for &from in &positions {
+            for &to in &positions {
+                let want = simple_path_risks(&map, from, to);
+                for k in [1, 3, want.len(), want.len() + 5] {
+                    let paths = map.lowest_risk_paths(from, to, k);
+                    let risks: Vec<usize> = paths.iter().map(|path| path.cost).collect();
+                    assert_eq!(risks, want[..k.min(want.len())], "{:?} to {:?}", from, to);
+                    for (i, path) in paths.iter().enumerate() {
+                        assert_eq!(
+                            (path.nodes[0], path.nodes[path.nodes.len() - 1]),
+                            (from, to)
+                        );
+                        let entered: usize = path.nodes[1..].iter().map(|&pos| map.risk(pos)).sum();
+                        assert_eq!(entered, path.cost);
+                        for (a, b) in path.nodes.iter().zip(&path.nodes[1..]) {
+                            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+                        }
+                        let mut nodes = path.nodes.clone();
+                        nodes.sort_unstable();
+                        nodes.dedup();
+                        assert_eq!(nodes.len(), path.nodes.len(), "{:?} revisits", path);
+                        assert!(!paths[..i].contains(path), "{:?} found twice", path);
+                    }
+                }
+                assert_eq!(map.lowest_risk(from, to), Some(want[0]));
+            }
+        }
+        // Corner to corner on a 3x3 grid, there are 12 simple paths.
+        assert_eq!(simple_path_risks(&map, (0, 0), (2, 2)).len(), 12);
+    }
+
+    #[test]
+    fn lowest_risk_paths_edge_cases() {
+        let cm: ChitonMap = "163\n138\n213".parse().unwrap();
+        let map = cm.tiled(1);
+        assert_eq!(map.lowest_risk_paths((0, 0), (2, 2), 0), []);
+        let paths = map.lowest_risk_paths((1, 1), (1, 1), 5);
+        assert_eq!(paths.len(), 1);
+        assert_eq!(
+            (paths[0].nodes.as_slice(), paths[0].cost),
+            (&[(1, 1)][..], 0)
+        );
+        assert_eq!(map.lowest_risk_paths((3, 0), (2, 2), 5), []);
+        assert_eq!(map.lowest_risk_paths((0, 0), (0, 3), 5), []);
+        assert_eq!(map.lowest_risk((1, 1), (1, 1)), Some(0));
+        assert_eq!(map.lowest_risk((0, 0), (5, 5)), None);
+    }
+
+    #[test]
+    fn risk_field_from_nearest_source() {
+        let cm: ChitonMap = "163\n138\n213".parse().unwrap();
+        let map = cm.tiled(2);
+        let sources = [(0, 0), (5, 2), (9, 9)];
+        let field = map.risk_field(&sources);
+        assert_eq!((field.width(), field.height()), (6, 6));
+        for pos in field.positions() {
+            let nearest = sources
+                .iter()
+                .filter_map(|&from| map.lowest_risk(from, pos))
+                .min();
+            assert_eq!(Some(field[pos]), nearest, "{:?}", pos);
+        }
+        let unreachable = map.risk_field(&[(6, 0)]);
+        assert!(unreachable.iter().all(|&risk| risk == usize::MAX));
+    }
+
+    #[test]
+    fn zero_risk_detours() {
+        let cm: ChitonMap = "0000\n1110\n0000\n0111\n0000".parse().unwrap();
+        let map = cm.tiled(1);
+        assert_eq!(map.min_risk(), 0);
+        assert_eq!(map.lowest_risk_path().unwrap().cost, 0);
+
+        let cm: ChitonMap = "060\n090\n090\n090\n090\n090\n000".parse().unwrap();
+        let map = cm.tiled(1);
+        assert_eq!(map.lowest_risk((0, 0), (2, 0)), Some(0));
+        assert_eq!(map.risk_field(&[(0, 0)])[(2, 0)], 0);
+
+        let tiled = cm.tiled(2);
+        assert_eq!(tiled.min_risk(), 0);
+        let field = tiled.risk_field(&[(0, 0)]);
+        for pos in field.positions() {
+            assert_eq!(
+                tiled.lowest_risk((0, 0), pos),
+                Some(field[pos]),
+                "{:?}",
+                pos
+            );
+        }
+        assert_eq!("89".parse::<ChitonMap>().unwrap().tiled(3).min_risk(), 1);
+        assert_eq!("5".parse::<ChitonMap>().unwrap().tiled(2).min_risk(), 5);
+    }
+
+    #[test]
+    fn tiled_risks_wrap_from_9_to_1() {
+        let cm: ChitonMap = "89".parse().unwrap();
+        let map = cm.tiled(3);
+        let top: Vec<usize> = (0..6).map(|x| map.risk((x, 0))).collect();
+        assert_eq!(top, [8, 9, 9, 1, 1, 2]);
+        let left: Vec<usize> = (0..3).map(|y| map.risk((0, y))).collect();
+        assert_eq!(left, [8, 9, 1]);
+        assert_eq!(map.risk((5, 2)), 4);
+        // Far enough out, risks wrap more than once.
+        let far = cm.tiled(10);
+        assert_eq!(far.risk((18, 9)), 8);
+        assert_eq!(far.risk((19, 9)), 9);
+    }
+}
@@ -0,0 +1,295 @@
+//! Day 16: Packet Decoder.
+
+use std::mem;
+
+use crate::parse::ParseError;
+use crate::Answer;
+use crate::Day;
+use crate::PuzzleError;
+
+pub struct Day16;
+
+impl Day for Day16 {
+    const DAY: u32 = 16;
+    const TITLE: &'static str = "Packet Decoder";
+    type Input = Vec<u8>;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError> {
+        Ok(decode_hex(input.trim_end())?)
+    }
+
+    fn part1(bytes: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle16_1(bytes)
+    }
+
+    fn part2(bytes: Self::Input) -> Result<Answer, PuzzleError> {
+        puzzle16_2(bytes)
+    }
+}
+
+fn puzzle16_1(bytes: Vec<u8>) -> Result<Answer, PuzzleError> {
+    let br = BitReader::new(&bytes[..]);
+    let mut pr = PacketReader::new(br);
+
+    let mut packets = Vec::<Packet>::new();
+    while let Some(p) = pr.next_packet()? {
+        packets.push(p);
+    }
+    fn visit(p: &Packet) -> u64 {
+        let mut version_sum = p.version as u64;
+        if let PacketBody::Operator { op: _, subpackets } = &p.body {
+            for sp in subpackets {
+                version_sum += visit(sp);
+            }
+        }
+        version_sum
+    }
+    let version_sum: u64 = packets.iter().map(visit).sum();
+    Ok(Answer::number(version_sum as i64))
+}
+
+fn puzzle16_2(bytes: Vec<u8>) -> Result<Answer, PuzzleError> {
+    let br = BitReader::new(&bytes[..]);
+    let mut pr = PacketReader::new(br);
+    let p = pr
+        .next_packet()?
+        .ok_or_else(|| PuzzleError::invalid("empty transmission"))?;
+    if pr.next_packet()?.is_some() {
+        return Err(PuzzleError::invalid("more than one outermost packet"));
+    }
+    let n = p.eval().map_err(PuzzleError::no_solution)?;
+    Ok(Answer::number(n as i64))
+}
+
+pub fn decode_hex(s: &str) -> Result<Vec<u8>, ParseError> {
+    let decode_digit = |i: usize, c: char| -> Result<u8, ParseError> {
+        if c.is_ascii_digit() {
+            Ok(c as u8 - b'0')
+        } else if ('A'..='F').contains(&c) {
+            Ok(c as u8 - b'A' + 10)
+        } else if ('a'..='f').contains(&c) {
+            Ok(c as u8 - b'a' + 10)
+        } else {
+            Err(ParseError::at(s, i, "hex digit"))
+        }
+    };
+    let mut bytes = Vec::<u8>::with_capacity(s.len() / 2 + s.len() % 2);
+    let mut hi: Option<u8> = None;
+    for (i, c) in s.char_indices() {
+        match hi {
+            None => {
+                hi = Some(decode_digit(i, c)?);
+            }
+            Some(hi_bits) => {
+                let lo_bits = decode_digit(i, c)?;
+                let b = hi_bits << 4 | lo_bits;
+                bytes.push(b);
+                hi = None;
+            }
+        }
+    }
+    if let Some(bits) = hi {
+        bytes.push(bits << 4);
+    }
+    Ok(bytes)
+}
+
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    bits_consumed: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader {
+            bytes,
+            bits_consumed: 0,
+        }
+    }
+
+    pub fn read(&mut self, mut nbits: usize) -> Result<usize, PuzzleError> {
+        if self.bits_consumed + nbits > self.bytes.len() * 8 {
+            return Err(self.error("transmission ends in the middle of a packet"));
+        }
+        assert!(nbits <= mem::size_of::<usize>() * 8);
+        let mut bits: usize = 0;
+        while nbits > 0 {
+            let nbits_from_this_byte = nbits.min(8 - self.bits_consumed % 8);
+            let shift = 8 - nbits_from_this_byte - self.bits_consumed % 8;
+            let mask = (1 << nbits_from_this_byte) - 1;
+            let bits_from_this_byte = (self.bytes[self.bits_consumed / 8] as usize >> shift) & mask;
+            bits = (bits << nbits_from_this_byte) | bits_from_this_byte;
+            nbits -= nbits_from_this_byte;
+            self.bits_consumed += nbits_from_this_byte;
+        }
+        Ok(bits)
+    }
+
+    /// Reports an error at the hex digit holding the next unread bit.
+    fn error(&self, message: &str) -> PuzzleError {
+        PuzzleError::invalid(message).at(1, self.bits_consumed / 4 + 1)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() * 8 - self.bits_consumed
+    }
+
+    /// Returns whether every unread bit is zero, as in the padding at the
+    /// end of a transmission.
+    fn only_padding_remains(&self) -> bool {
+        let i = self.bits_consumed / 8;
+        match self.bytes.get(i) {
+            None => true,
+            Some(b) => {
+                let unread = b & (0xFFu8 >> (self.bits_consumed % 8));
+                unread == 0 && self.bytes[i + 1..].iter().all(|b| *b == 0)
+            }
+        }
+    }
+}
+
+pub struct Packet {
+    pub version: u8,
+    pub body: PacketBody,
+}
+
+pub enum PacketBody {
+    Literal(usize),
+    Operator {
+        op: PacketOp,
+        subpackets: Vec<Packet>,
+    },
+}
+
+pub enum PacketOp {
+    Sum,
+    Product,
+    Minimum,
+    Maximum,
+    GreaterThan,
+    LessThan,
+    EqualTo,
+}
+
+impl Packet {
+    pub fn eval(&self) -> Result<usize, String> {
+        match &self.body {
+            PacketBody::Literal(n) => Ok(*n),
+            PacketBody::Operator { op, subpackets } => {
+                let values = subpackets
+                    .iter()
+                    .map(|p| p.eval())
+                    .collect::<Result<Vec<_>, _>>()?;
+                let overflow = || String::from("packet value overflows");
+                let empty = || String::from("minimum or maximum of no subpackets");
+                match op {
+                    PacketOp::Sum => values
+                        .iter()
+                        .try_fold(0usize, |acc, v| acc.checked_add(*v))
+                        .ok_or_else(overflow),
+                    PacketOp::Product => values
+                        .iter()
+                        .try_fold(1usize, |acc, v| acc.checked_mul(*v))
+                        .ok_or_else(overflow),
+                    PacketOp::Minimum => values.into_iter().min().ok_or_else(empty),
+                    PacketOp::Maximum => values.into_iter().max().ok_or_else(empty),
+                    _ => {
+                        let (x, y) = match values[..] {
+                            [x, y] => (x, y),
+                            _ => {
+                                return Err(format!(
+                                    "comparison needs 2 subpackets, found {}",
+                                    values.len()
+                                ))
+                            }
+                        };
+                        Ok((match op {
+                            PacketOp::GreaterThan => x > y,
+                            PacketOp::LessThan => x < y,
+                            _ => x == y,
+                        }) as usize)
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct PacketReader<'a> {
+    r: BitReader<'a>,
+}
+
+impl<'a> PacketReader<'a> {
+    pub fn new(r: BitReader) -> PacketReader {
+        PacketReader { r }
+    }
+
+    /// Reads the next packet, or returns None if only padding is left.
+    pub fn next_packet(&mut self) -> Result<Option<Packet>, PuzzleError> {
+        if self.r.remaining() < 6 || self.r.only_padding_remains() {
+            // zeroes padding end of stream.
+            return Ok(None);
+        }
+        let version = self.r.read(3)? as u8;
+        let type_id = self.r.read(3)? as u8;
+        let body = match type_id {
+            4 => {
+                let mut n = 0;
+                let mut width = 0;
+                loop {
+                    let next = self.r.read(5)?;
+                    width += 4;
+                    if width > mem::size_of::<usize>() * 8 {
+                        return Err(self.r.error("literal value too large"));
+                    }
+                    n = (n << 4) | (next & 0xF);
+                    if (next & 0x10) == 0 {
+                        break;
+                    }
+                }
+                PacketBody::Literal(n)
+            }
+            _ => {
+                let op = match type_id {
+                    0 => PacketOp::Sum,
+                    1 => PacketOp::Product,
+                    2 => PacketOp::Minimum,
+                    3 => PacketOp::Maximum,
+                    5 => PacketOp::GreaterThan,
+                    6 => PacketOp::LessThan,
+                    _ => PacketOp::EqualTo,
+                };
+                let length_type_id = self.r.read(1)?;
+                let subpackets = if length_type_id == 0 {
+                    let subpacket_length_in_bits = self.r.read(15)?;
+                    let end = self.r.bits_consumed + subpacket_length_in_bits;
+                    let mut subpackets = Vec::<Packet>::new();
+                    while self.r.bits_consumed < end {
+                        subpackets.push(self.subpacket()?);
+                    }
+                    if self.r.bits_consumed != end {
+                        return Err(self.r.error(&format!(
+                            "subpackets overrun their declared length of {} bits",
+                            subpacket_length_in_bits
+                        )));
+                    }
+                    subpackets
+                } else {
+                    let subpacket_count = self.r.read(11)?;
+                    let mut subpackets = Vec::<Packet>::with_capacity(subpacket_count);
+                    for _ in 0..subpacket_count {
+                        subpackets.push(self.subpacket()?);
+                    }
+                    subpackets
+                };
+                PacketBody::Operator { op, subpackets }
+            }
+        };
+        Ok(Some(Packet { version, body }))
+    }
+
+    fn subpacket(&mut self) -> Result<Packet, PuzzleError> {
+        self.next_packet()?
+            .ok_or_else(|| self.r.error("transmission ends before all subpackets"))
+    }
+}
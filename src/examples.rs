@@ -0,0 +1,387 @@
+//! The worked examples from each day's puzzle description, run through the
+//! same parsers and solvers as real inputs.
+
+use crate::registry;
+use crate::Answer;
+use crate::AnswerValue;
+
+/// Solves the puzzle with `id`, like "7_2", for `input`.
+fn solve(id: &str, input: &str) -> Answer {
+    let registry = registry();
+    let puzzle = match registry.select(id)[..] {
+        [puzzle] => puzzle,
+        _ => panic!("no puzzle {}", id),
+    };
+    match puzzle.solve(input) {
+        Ok(answer) => answer,
+        Err(err) => panic!("{} failed: {}", id, err),
+    }
+}
+
+fn number(id: &str, input: &str) -> i64 {
+    match solve(id, input).value {
+        AnswerValue::Number(n) => n,
+        AnswerValue::Text(text) => panic!("{} answered text:\n{}", id, text),
+    }
+}
+
+fn text(id: &str, input: &str) -> String {
+    match solve(id, input).value {
+        AnswerValue::Text(text) => text,
+        AnswerValue::Number(n) => panic!("{} answered number {}", id, n),
+    }
+}
+
+pub(crate) const DEPTHS: &str = "199
+200
+208
+210
+200
+207
+240
+269
+260
+263
+";
+
+#[test]
+fn day1() {
+    assert_eq!(number("1_1", DEPTHS), 7);
+    assert_eq!(number("1_2", DEPTHS), 5);
+}
+
+pub(crate) const COMMANDS: &str = "forward 5
+down 5
+forward 8
+up 3
+down 8
+forward 2
+";
+
+#[test]
+fn day2() {
+    let want = Answer::number(150).with("hpos", 15).with("depth", 10);
+    assert_eq!(solve("2_1", COMMANDS), want);
+    let want = Answer::number(900).with("hpos", 15).with("depth", 60);
+    assert_eq!(solve("2_2", COMMANDS), want);
+}
+
+const DIAGNOSTIC_REPORT: &str = "00100
+11110
+10110
+10111
+10101
+01111
+00111
+11100
+10000
+11001
+00010
+01010
+";
+
+#[test]
+fn day3() {
+    let want = Answer::number(198)
+        .with("gamma_rate", 22)
+        .with("epsilon_rate", 9);
+    assert_eq!(solve("3_1", DIAGNOSTIC_REPORT), want);
+    let want = Answer::number(230)
+        .with("o2_generator_rating", 23)
+        .with("co2_scrubber_rating", 10);
+    assert_eq!(solve("3_2", DIAGNOSTIC_REPORT), want);
+}
+
+const BINGO: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+
+22 13 17 11  0
+ 8  2 23  4 24
+21  9 14 16  7
+ 6 10  3 18  5
+ 1 12 20 15 19
+
+ 3 15  0  2 22
+ 9 18 13 17  5
+19  8  7 25 23
+20 11 10 24  4
+14 21 16 12  6
+
+14 21 17 24  4
+10 16 15  9 19
+18  8 23 26 20
+22 11 13  6  5
+ 2  0 12  3  7
+";
+
+#[test]
+fn day4() {
+    assert_eq!(number("4_1", BINGO), 4512);
+    assert_eq!(number("4_2", BINGO), 1924);
+}
+
+const VENTS: &str = "0,9 -> 5,9
+8,0 -> 0,8
+9,4 -> 3,4
+2,2 -> 2,1
+7,0 -> 7,4
+6,4 -> 2,0
+0,9 -> 2,9
+3,4 -> 1,4
+0,0 -> 8,8
+5,5 -> 8,2
+";
+
+#[test]
+fn day5() {
+    assert_eq!(number("5_1", VENTS), 5);
+    assert_eq!(number("5_2", VENTS), 12);
+}
+
+const LANTERNFISH: &str = "3,4,3,1,2
+";
+
+#[test]
+fn day6() {
+    assert_eq!(number("6_1", LANTERNFISH), 5934);
+    assert_eq!(number("6_2", LANTERNFISH), 26984457539);
+}
+
+const CRABS: &str = "16,1,2,0,4,2,7,1,2,14
+";
+
+#[test]
+fn day7() {
+    assert_eq!(solve("7_1", CRABS), Answer::number(37).with("min_pos", 2));
+    assert_eq!(solve("7_2", CRABS), Answer::number(168).with("min_pos", 5));
+}
+
+const DISPLAYS: &str =
+    "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
+edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
+fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
+fbegcd cbd adcefb dageb afcb bc aefdc ecdab fgdeca fcdbega | efabcd cedba gadfec cb
+aecbfdg fbg gf bafeg dbefa fcge gcbea fcaegb dgceab fcbdga | gecf egdcabf bgf bfgea
+fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+dbcfg fgd bdegcaf fgec aegbdf ecdfab fbedc dacgb gdcebf gf | cefg dcbef fcge gbcadfe
+bdfegc cbegaf gecbf dfcage bdacg ed bedf ced adcbefg gebcd | ed bcgafe cdgba cbgef
+egadfb cdbfeg cegd fecab cgb gbdefca cg fgcdab egfdb bfceg | gbdfcae bgc cg cgb
+gcafb gcf dcaebfg ecagb gf abcdeg gaef cafbge fdbac fegbdc | fgae cfgab fg bagce
+";
+
+#[test]
+fn day8() {
+    assert_eq!(number("8_1", DISPLAYS), 26);
+    assert_eq!(number("8_2", DISPLAYS), 61229);
+}
+
+const HEIGHTS: &str = "2199943210
+3987894921
+9856789892
+8767896789
+9899965678
+";
+
+#[test]
+fn day9() {
+    assert_eq!(number("9_1", HEIGHTS), 15);
+    assert_eq!(number("9_2", HEIGHTS), 1134);
+}
+
+const NAVIGATION: &str = "[({(<(())[]>[[{[]{<()<>>
+[(()[<>])]({[<{<<[]>>(
+{([(<{}[<>[]}>{[]{[(<()>
+(((({<>}<{<{<>}{[]{[]{}
+[[<[([]))<([[{}[[()]]]
+[{[{({}]{}}([{[{{{}}([]
+{<[[]]>}<{[{[{[]{()[[[]
+[<(<(<(<{}))><([]([]()
+<{([([[(<>()){}]>(<<{{
+<{([{{}}[<[[[<>{}]]]>[]]
+";
+
+#[test]
+fn day10() {
+    assert_eq!(number("10_1", NAVIGATION), 26397);
+    assert_eq!(number("10_2", NAVIGATION), 288957);
+}
+
+const OCTOPUSES: &str = "5483143223
+2745854711
+5264556173
+6141336146
+6357385478
+4167524645
+2176841721
+6882881134
+4846848554
+5283751526
+";
+
+#[test]
+fn day11() {
+    assert_eq!(number("11_1", OCTOPUSES), 1656);
+    assert_eq!(number("11_2", OCTOPUSES), 195);
+}
+
+const SMALL_CAVES: &str = "start-A
+start-b
+A-c
+A-b
+b-d
+A-end
+b-end
+";
+
+const MEDIUM_CAVES: &str = "dc-end
+HN-start
+start-kj
+dc-start
+dc-HN
+LN-dc
+HN-end
+kj-sk
+kj-HN
+kj-dc
+";
+
+const LARGE_CAVES: &str = "fs-end
+he-DX
+fs-he
+start-DX
+pj-DX
+end-zg
+zg-sl
+zg-pj
+pj-he
+RW-he
+fs-DX
+pj-RW
+zg-RW
+start-pj
+he-WI
+zg-he
+pj-fs
+start-RW
+";
+
+#[test]
+fn day12() {
+    assert_eq!(number("12_1", SMALL_CAVES), 10);
+    assert_eq!(number("12_2", SMALL_CAVES), 36);
+    assert_eq!(number("12_1", MEDIUM_CAVES), 19);
+    assert_eq!(number("12_2", MEDIUM_CAVES), 103);
+    assert_eq!(number("12_1", LARGE_CAVES), 226);
+    assert_eq!(number("12_2", LARGE_CAVES), 3509);
+}
+
+const PAPER: &str = "6,10
+0,14
+9,10
+0,3
+10,4
+4,11
+6,0
+6,12
+4,1
+0,13
+10,12
+3,4
+3,0
+8,4
+1,10
+2,14
+8,10
+9,0
+
+fold along y=7
+fold along x=5
+";
+
+#[test]
+fn day13() {
+    assert_eq!(number("13_1", PAPER), 17);
+    let square = "#####
+#...#
+#...#
+#...#
+#####";
+    assert_eq!(text("13_2", PAPER), square);
+}
+
+const POLYMER: &str = "NNCB
+
+CH -> B
+HH -> N
+CB -> H
+NH -> C
+HB -> C
+HC -> B
+HN -> C
+NN -> C
+BH -> H
+NC -> B
+NB -> B
+BN -> B
+BB -> N
+BC -> B
+CC -> N
+CN -> C
+";
+
+#[test]
+fn day14() {
+    assert_eq!(number("14_1", POLYMER), 1588);
+    assert_eq!(number("14_2", POLYMER), 2188189693529);
+}
+
+const CHITONS: &str = "1163751742
+1381373672
+2136511328
+3694931569
+7463417111
+1319128137
+1359912421
+3125421639
+1293138521
+2311944581
+";
+
+#[test]
+fn day15() {
+    assert_eq!(number("15_1", CHITONS), 40);
+    assert_eq!(number("15_2", CHITONS), 315);
+}
+
+#[test]
+fn day16_version_sums() {
+    let examples = [
+        ("D2FE28", 6),
+        ("38006F45291200", 9),
+        ("EE00D40C823060", 14),
+        ("8A004A801A8002F478", 16),
+        ("620080001611562C8802118E34", 12),
+        ("C0015000016115A2E0802F182340", 23),
+        ("A0016C880162017C3686B18A3D4780", 31),
+    ];
+    for (hex, version_sum) in examples {
+        assert_eq!(number("16_1", hex), version_sum, "{}", hex);
+    }
+}
+
+#[test]
+fn day16_expressions() {
+    let examples = [
+        ("D2FE28", 2021),
+        ("C200B40A82", 3),
+        ("04005AC33890", 54),
+        ("880086C3E88112", 7),
+        ("CE00C43D881120", 9),
+        ("D8005AC2A8F0", 1),
+        ("F600BC2D8F", 0),
+        ("9C005AC2F8F0", 0),
+        ("9C0141080250320F1802104A08", 1),
+    ];
+    for (hex, value) in examples {
+        assert_eq!(number("16_2", hex), value, "{}", hex);
+    }
+}
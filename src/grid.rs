@@ -0,0 +1,429 @@
+//! A rectangular grid of cells, and ways of moving around one.
+
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::slice;
+use std::str::FromStr;
+
+use crate::parse::offset_in;
+use crate::parse::ParseError;
+
+/// A rectangular grid of cells, stored row by row. Cells are addressed by
+/// (x, y), with x increasing to the right and y increasing downward.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid with every cell set to `value`.
+    ///
+    /// Panics if the number of cells overflows a usize.
+    pub fn new(width: usize, height: usize, value: T) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_cells(width, height, vec![value; Grid::<T>::len(width, height)])
+    }
+
+    /// Creates a grid from its cells, listed row by row.
+    ///
+    /// Panics if there aren't `width` times `height` cells.
+    pub fn from_cells(width: usize, height: usize, cells: Vec<T>) -> Grid<T> {
+        assert_eq!(cells.len(), Grid::<T>::len(width, height));
+        Grid {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    fn len(width: usize, height: usize) -> usize {
+        match width.checked_mul(height) {
+            Some(len) => len,
+            None => panic!("{}x{} grid has too many cells", width, height),
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    fn index_of(&self, x: usize, y: usize) -> Option<usize> {
+        if x < self.width && y < self.height {
+            Some(y * self.width + x)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the cell at (x, y), or None if that's outside the grid.
+    pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+        self.index_of(x, y).map(|i| &self.cells[i])
+    }
+
+    /// Returns the cell at (x, y), or None if that's outside the grid.
+    pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+        self.index_of(x, y).map(|i| &mut self.cells[i])
+    }
+
+    /// Returns the cells row by row.
+    pub fn iter(&self) -> slice::Iter<'_, T> {
+        self.cells.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, T> {
+        self.cells.iter_mut()
+    }
+
+    /// Returns every (x, y) in the grid, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Returns the rows from top to bottom, each running left to right.
+    pub fn rows(&self) -> slice::Chunks<'_, T> {
+        // chunks() doesn't allow a size of 0, but then there are no cells
+        // anyway.
+        self.cells.chunks(self.width.max(1))
+    }
+
+    /// Returns the columns from left to right, each running top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(move |x| (0..self.height).map(move |y| &self.cells[y * self.width + x]))
+    }
+
+    /// Returns the neighbors of (x, y) that are inside the grid.
+    pub fn neighbors(&self, x: usize, y: usize, connectivity: Connectivity) -> Neighbors {
+        Neighbors::new(self.width(), self.height(), x, y, connectivity, false)
+    }
+
+    /// Returns the neighbors of (x, y), with each edge of the grid wrapping
+    /// around to the opposite edge, so every cell has a full set. On grids
+    /// less than 3 cells wide or high, a cell can be its own neighbor or the
+    /// same neighbor more than once. On an empty grid, there are none.
+    pub fn wrapping_neighbors(&self, x: usize, y: usize, connectivity: Connectivity) -> Neighbors {
+        Neighbors::new(self.width(), self.height(), x, y, connectivity, true)
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        match self.get(x, y) {
+            Some(cell) => cell,
+            None => panic!(
+                "({}, {}) is outside {}x{} grid",
+                x, y, self.width, self.height
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        let (width, height) = (self.width, self.height);
+        match self.get_mut(x, y) {
+            Some(cell) => cell,
+            None => panic!("({}, {}) is outside {}x{} grid", x, y, width, height),
+        }
+    }
+}
+
+/// Which of the cells around a cell count as its neighbors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Connectivity {
+    /// The cells above, left, below, and right.
+    Orthogonal,
+
+    /// The four cells touching only at a corner.
+    Diagonal,
+
+    /// All eight surrounding cells.
+    All,
+}
+
+impl Connectivity {
+    /// Returns the (dx, dy) offsets of the neighbors, going counterclockwise
+    /// as seen with y increasing downward: from the top for Orthogonal and
+    /// All, and from the top left for Diagonal.
+    pub fn offsets(self) -> &'static [(isize, isize)] {
+        match self {
+            Connectivity::Orthogonal => &[(0, -1), (-1, 0), (0, 1), (1, 0)],
+            Connectivity::Diagonal => &[(-1, -1), (-1, 1), (1, 1), (1, -1)],
+            Connectivity::All => &[
+                (0, -1),
+                (-1, -1),
+                (-1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, -1),
+            ],
+        }
+    }
+}
+
+/// An iterator over the neighbors of a grid cell. It only holds the grid's
+/// size, so the grid can be changed while iterating.
+pub struct Neighbors {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+    offsets: slice::Iter<'static, (isize, isize)>,
+    wrap: bool,
+}
+
+impl Neighbors {
+    /// Returns the neighbors of (x, y) in a grid `width` wide and `height`
+    /// tall. If `wrap` is set, each edge of the grid wraps around to the
+    /// opposite one, except in an empty grid, where there are no neighbors.
+    pub fn new(
+        width: usize,
+        height: usize,
+        x: usize,
+        y: usize,
+        connectivity: Connectivity,
+        wrap: bool,
+    ) -> Neighbors {
+        Neighbors {
+            x,
+            y,
+            width,
+            height,
+            offsets: connectivity.offsets().iter(),
+            wrap,
+        }
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for &(dx, dy) in &mut self.offsets {
+            if self.wrap {
+                if self.width == 0 || self.height == 0 {
+                    return None;
+                }
+                let x = (self.x + self.width).wrapping_add_signed(dx) % self.width;
+                let y = (self.y + self.height).wrapping_add_signed(dy) % self.height;
+                return Some((x, y));
+            }
+            match (self.x.checked_add_signed(dx), self.y.checked_add_signed(dy)) {
+                (Some(x), Some(y)) if x < self.width && y < self.height => return Some((x, y)),
+                _ => (),
+            }
+        }
+        None
+    }
+}
+
+/// Parses a grid of single digits, one row per line. Every row must be as
+/// long as the first.
+impl<T: From<u8>> FromStr for Grid<T> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut width = 0;
+        let mut height = 0;
+        let mut cells = Vec::<T>::new();
+        for line in s.trim().lines() {
+            // Digits are checked first, so that rows can be compared by
+            // their length in bytes.
+            for (i, c) in line.char_indices() {
+                if !c.is_ascii_digit() {
+                    return Err(ParseError::at(s, offset_in(s, line) + i, "digit"));
+                }
+                cells.push(T::from(c as u8 - b'0'))
+            }
+            if height == 0 {
+                width = line.len();
+            } else if line.len() != width {
+                let expected = format!("row of {} digits like the first", width);
+                return Err(ParseError::at_str(s, line, expected));
+            }
+            height += 1;
+        }
+        Ok(Grid {
+            width,
+            height,
+            cells,
+        })
+    }
+}
+
+/// Formats the grid one row per line, with nothing between cells.
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut sep = "";
+        for row in self.rows() {
+            f.write_str(sep)?;
+            sep = "\n";
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grid(s: &str) -> Grid<u8> {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn get_outside_grid() {
+        let mut g = grid("123\n456");
+        assert_eq!(g.get(2, 1), Some(&6));
+        assert_eq!(g.get(3, 0), None);
+        assert_eq!(g.get(0, 2), None);
+        assert_eq!(g.get(usize::MAX, usize::MAX), None);
+        *g.get_mut(0, 1).unwrap() = 9;
+        assert_eq!(g[(0, 1)], 9);
+        assert!(g.get_mut(3, 1).is_none());
+        assert!(g.get_mut(1, 2).is_none());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let g = grid("123\n456");
+        let rows: Vec<&[u8]> = g.rows().collect();
+        assert_eq!(rows, [[1, 2, 3], [4, 5, 6]]);
+        let columns: Vec<Vec<u8>> = g.columns().map(|c| c.copied().collect()).collect();
+        assert_eq!(columns, [[1, 4], [2, 5], [3, 6]]);
+        let positions: Vec<(usize, usize)> = g.positions().collect();
+        assert_eq!(positions, [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]);
+
+        // A grid with no rows still has columns, all empty.
+        let flat = Grid::new(3, 0, 0u8);
+        assert_eq!(
+            flat.columns().map(|c| c.count()).collect::<Vec<_>>(),
+            [0, 0, 0]
+        );
+        assert_eq!(flat.rows().count(), 0);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = "12\n1x".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 2, "'x'"));
+
+        // A bad digit is reported as such, even if it makes the row longer.
+        let err = "12\n1é".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 2, "'é'"));
+
+        let err = "123\n123\n12\n123".parse::<Grid<u8>>().unwrap_err();
+        assert_eq!((err.line, err.column, err.found.as_str()), (3, 1, "'12'"));
+        assert_eq!(err.expected, "row of 3 digits like the first");
+
+        let g: Grid<u8> = "".parse().unwrap();
+        assert!(g.is_empty());
+        assert_eq!((g.width(), g.height()), (0, 0));
+        assert_eq!(g.rows().count(), 0);
+    }
+
+    #[test]
+    fn display_round_trip() {
+        let text = "0123\n4567\n8901";
+        let g = grid(text);
+        assert_eq!((g.width(), g.height()), (4, 3));
+        assert_eq!(g.to_string(), text);
+        assert_eq!(grid(&g.to_string()), g);
+    }
+
+    fn neighbors(g: &Grid<u8>, x: usize, y: usize, c: Connectivity) -> Vec<(usize, usize)> {
+        g.neighbors(x, y, c).collect()
+    }
+
+    fn wrapping(g: &Grid<u8>, x: usize, y: usize, c: Connectivity) -> Vec<(usize, usize)> {
+        g.wrapping_neighbors(x, y, c).collect()
+    }
+
+    #[test]
+    fn neighbors_at_corners_and_edges() {
+        let g = Grid::new(3, 3, 0);
+        assert_eq!(
+            neighbors(&g, 0, 0, Connectivity::Orthogonal),
+            [(0, 1), (1, 0)]
+        );
+        assert_eq!(neighbors(&g, 0, 0, Connectivity::Diagonal), [(1, 1)]);
+        assert_eq!(
+            neighbors(&g, 0, 0, Connectivity::All),
+            [(0, 1), (1, 1), (1, 0)]
+        );
+        assert_eq!(neighbors(&g, 2, 2, Connectivity::Diagonal), [(1, 1)]);
+        assert_eq!(
+            neighbors(&g, 1, 0, Connectivity::Orthogonal),
+            [(0, 0), (1, 1), (2, 0)]
+        );
+        assert_eq!(neighbors(&g, 1, 1, Connectivity::All).len(), 8);
+        assert_eq!(
+            neighbors(&g, 1, 1, Connectivity::Diagonal),
+            [(0, 0), (0, 2), (2, 2), (2, 0)]
+        );
+
+        let line = Grid::new(1, 3, 0);
+        assert_eq!(neighbors(&line, 0, 1, Connectivity::All), [(0, 0), (0, 2)]);
+        assert_eq!(neighbors(&line, 0, 0, Connectivity::Diagonal), []);
+    }
+
+    #[test]
+    fn wrapping_neighbors() {
+        let g = Grid::new(3, 3, 0);
+        assert_eq!(
+            wrapping(&g, 0, 0, Connectivity::All),
+            [
+                (0, 2),
+                (2, 2),
+                (2, 0),
+                (2, 1),
+                (0, 1),
+                (1, 1),
+                (1, 0),
+                (1, 2)
+            ]
+        );
+
+        // In a single column, moving sideways wraps back to the same cell.
+        let line = Grid::new(1, 3, 0);
+        assert_eq!(
+            wrapping(&line, 0, 0, Connectivity::Orthogonal),
+            [(0, 2), (0, 0), (0, 1), (0, 0)]
+        );
+
+        // In a 2x2 grid, both ways along an axis lead to the same cell.
+        let square = Grid::new(2, 2, 0);
+        assert_eq!(
+            wrapping(&square, 0, 0, Connectivity::Orthogonal),
+            [(0, 1), (1, 0), (0, 1), (1, 0)]
+        );
+        assert_eq!(wrapping(&square, 1, 1, Connectivity::Diagonal), [(0, 0); 4]);
+
+        for (width, height) in [(0, 0), (0, 3), (3, 0)] {
+            let empty = Grid::new(width, height, 0);
+            assert_eq!(wrapping(&empty, 0, 0, Connectivity::All), []);
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Solutions to the Advent of Code 2021 puzzles.
+//!
+//! Each day has its own module, with a type implementing [`Day`] that parses
+//! the day's input and solves both parts, along with the types the input
+//! parses into. [`registry`] collects every day's puzzles so they can be
+//! looked up and run by name, which is what the command line tool does.
+
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day16;
+pub mod grid;
+pub mod parse;
+pub mod pathfinding;
+mod puzzle;
+
+#[cfg(test)]
+mod examples;
+#[cfg(test)]
+mod properties;
+
+pub use puzzle::Answer;
+pub use puzzle::AnswerValue;
+pub use puzzle::Day;
+pub use puzzle::Puzzle;
+pub use puzzle::PuzzleError;
+pub use puzzle::PuzzleErrorKind;
+pub use puzzle::Registry;
+pub use puzzle::RenderOptions;
+pub use puzzle::Rendering;
+pub use puzzle::Timing;
+
+/// Returns a registry containing every puzzle that has been solved so far.
+pub fn registry() -> Registry {
+    let mut r = Registry::new();
+    r.register::<day01::Day1>();
+    r.register::<day02::Day2>();
+    r.register::<day03::Day3>();
+    r.register::<day04::Day4>();
+    r.register::<day05::Day5>();
+    r.register::<day06::Day6>();
+    r.register::<day07::Day7>();
+    r.register::<day08::Day8>();
+    r.register::<day09::Day9>();
+    r.register::<day10::Day10>();
+    r.register::<day11::Day11>();
+    r.register::<day12::Day12>();
+    r.register::<day13::Day13>();
+    r.register::<day14::Day14>();
+    r.register::<day15::Day15>();
+    r.register::<day16::Day16>();
+    r
+}
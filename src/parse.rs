@@ -0,0 +1,201 @@
+//! Errors and helpers for parsing puzzle input.
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+
+/// An error parsing puzzle input: where in the input it happened, what the
+/// parser expected there, and what it found instead.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// Line where the problem was found, starting at 1.
+    pub line: usize,
+
+    /// Column where the problem was found, in characters, starting at 1.
+    pub column: usize,
+
+    /// What the parser wanted, like "integer" or "','".
+    pub expected: String,
+
+    /// What was there instead: the quoted text, "end of line", or "end of
+    /// input".
+    pub found: String,
+
+    /// Byte offset and length of the offending text in the parsed string.
+    offset: usize,
+    len: usize,
+
+    /// The line containing the offending text, for snippets.
+    text: String,
+}
+
+impl ParseError {
+    /// Creates an error at byte offset `pos` in `input`. The character there
+    /// is reported as what was found.
+    pub fn at(input: &str, pos: usize, expected: impl Into<String>) -> ParseError {
+        ParseError::spanning(input, pos, 0, expected.into())
+    }
+
+    /// Creates an error reporting `part`, which must be a slice of `input`,
+    /// as what was found.
+    pub fn at_str(input: &str, part: &str, expected: impl Into<String>) -> ParseError {
+        ParseError::spanning(input, offset_in(input, part), part.len(), expected.into())
+    }
+
+    /// Moves an error from parsing `part`, a slice of `input`, to where it
+    /// is in `input`.
+    pub fn within(self, input: &str, part: &str) -> ParseError {
+        let pos = offset_in(input, part) + self.offset;
+        ParseError::spanning(input, pos, self.len, self.expected)
+    }
+
+    fn spanning(input: &str, pos: usize, len: usize, expected: String) -> ParseError {
+        let line_start = input[..pos].rfind('\n').map_or(0, |i| i + 1);
+        let line_end = input[pos..].find('\n').map_or(input.len(), |i| pos + i);
+        let text = input[line_start..line_end].trim_end_matches('\r');
+        let found = match input[pos..].chars().next() {
+            None => String::from("end of input"),
+            Some('\n' | '\r') => String::from("end of line"),
+            Some(c) if len == 0 => format!("'{}'", c),
+            Some(_) => format!("'{}'", &input[pos..(pos + len).min(line_end)]),
+        };
+        ParseError {
+            line: input[..pos].matches('\n').count() + 1,
+            column: input[line_start..pos].chars().count() + 1,
+            expected,
+            found,
+            offset: pos,
+            len,
+            text: String::from(text),
+        }
+    }
+
+    /// Renders the offending line with the problem underlined, like
+    ///
+    /// ```text
+    /// 3 | 0,9 -> x,9
+    ///   |        ^
+    /// ```
+    pub fn snippet(&self) -> String {
+        let start = self
+            .text
+            .char_indices()
+            .nth(self.column - 1)
+            .map_or(self.text.len(), |(i, _)| i);
+        let end = (start + self.len).min(self.text.len());
+        let width = self.text[start..end].chars().count().max(1);
+        let gutter = self.line.to_string().len();
+        format!(
+            "{:>gutter$} | {}\n{:>gutter$} | {:>column$}\n",
+            self.line,
+            self.text,
+            "",
+            "^".repeat(width),
+            gutter = gutter,
+            column = self.column - 1 + width,
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found {}",
+            self.line, self.column, self.expected, self.found
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Returns the byte offset of `part` in `s`. `part` must be a slice of `s`.
+pub fn offset_in(s: &str, part: &str) -> usize {
+    let offset = (part.as_ptr() as usize).wrapping_sub(s.as_ptr() as usize);
+    assert!(offset <= s.len(), "not a slice of the parsed string");
+    offset
+}
+
+/// Parses a whitespace-separated list of integers.
+pub fn parse_space_separated<T: std::str::FromStr>(s: &str) -> Result<Vec<T>, ParseError> {
+    s.split_ascii_whitespace()
+        .map(|w| {
+            w.parse::<T>()
+                .map_err(|_| ParseError::at_str(s, w, "integer"))
+        })
+        .collect()
+}
+
+/// Parses a list of integers separated by `sep`.
+pub fn parse_separated<T: std::str::FromStr>(s: &str, sep: &str) -> Result<Vec<T>, ParseError> {
+    s.split(sep)
+        .map(|w| {
+            w.parse::<T>()
+                .map_err(|_| ParseError::at_str(s, w, "integer"))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snippet_after_multibyte_characters() {
+        let input = "ab\né,ü,xyz\n";
+        let err = ParseError::at_str(input, &input[9..12], "integer");
+        assert_eq!((err.line, err.column), (2, 5));
+        assert_eq!(err.found, "'xyz'");
+        assert_eq!(err.snippet(), "2 | é,ü,xyz\n  |     ^^^\n");
+
+        let err = ParseError::at(input, 5, "digit");
+        assert_eq!((err.column, err.found.as_str()), (2, "','"));
+        assert_eq!(err.snippet(), "2 | é,ü,xyz\n  |  ^\n");
+    }
+
+    #[test]
+    fn snippet_at_end_of_line() {
+        let input = "12\r\n34";
+        let err = ParseError::at(input, 2, "digit");
+        assert_eq!((err.line, err.column), (1, 3));
+        assert_eq!(err.found, "end of line");
+        assert_eq!(err.snippet(), "1 | 12\n  |   ^\n");
+    }
+
+    #[test]
+    fn snippet_at_end_of_input() {
+        let input = "12\n34";
+        let err = ParseError::at(input, input.len(), "','");
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.found, "end of input");
+        assert_eq!(err.snippet(), "2 | 34\n  |   ^\n");
+    }
+
+    #[test]
+    fn snippet_gutter_fits_line_number() {
+        let input = format!("{}x", "1\n".repeat(11));
+        let err = ParseError::at(&input, input.len() - 1, "digit");
+        assert_eq!(err.snippet(), "12 | x\n   | ^\n");
+    }
+
+    #[test]
+    fn error_within_part() {
+        let input = "1,2\n3,é4";
+        let part = &input[4..];
+        let err = ParseError::at_str(part, &part[2..], "integer").within(input, part);
+        assert_eq!((err.line, err.column, err.found.as_str()), (2, 3, "'é4'"));
+    }
+
+    #[test]
+    fn separated_numbers() {
+        assert_eq!(
+            parse_separated::<i64>("3,4,3,1,2", ","),
+            Ok(vec![3, 4, 3, 1, 2])
+        );
+        let err = parse_separated::<i64>("3,4,x,1", ",").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+        let err = parse_space_separated::<i64>("3 4\n 1 y").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+    }
+}
@@ -0,0 +1,550 @@
+//! Shortest paths through weighted graphs.
+//!
+//! Graphs are given implicitly, by a function returning each node's
+//! neighbors along with the cost of moving to them, so the same searches
+//! work on grids, cave maps, and anything else with hashable nodes.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::hash::Hash;
+
+/// A cheapest path found by a search.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path<N> {
+    /// The nodes along the path, from a start node to a goal node.
+    pub nodes: Vec<N>,
+
+    /// The total cost of the moves along the path.
+    pub cost: usize,
+}
+
+/// Finds a cheapest path from any of `starts` to any node for which
+/// `is_goal` returns true, using Dijkstra's algorithm. Returns None if no
+/// goal can be reached.
+pub fn dijkstra<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    is_goal: impl FnMut(&N) -> bool,
+    neighbors: impl FnMut(&N) -> I,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    a_star(starts, is_goal, neighbors, |_| 0)
+}
+
+/// Finds a cheapest path from any of `starts` to any node for which
+/// `is_goal` returns true, using A*. `heuristic` estimates the cost from a
+/// node to the nearest goal. The path is only guaranteed to be cheapest if
+/// the estimate is never too high. Returns None if no goal can be reached.
+pub fn a_star<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    is_goal: impl FnMut(&N) -> bool,
+    neighbors: impl FnMut(&N) -> I,
+    heuristic: impl FnMut(&N) -> usize,
+) -> Option<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let (seen, goal) = search(Seen::new(), starts, is_goal, neighbors, heuristic);
+    goal.map(|i| seen.path_to(i))
+}
+
+/// Returns the cost of a cheapest path from any of `starts` to every node
+/// that can be reached from them.
+pub fn distances<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbors: impl FnMut(&N) -> I,
+) -> HashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let (seen, _) = search(Seen::new(), starts, |_| false, neighbors, |_| 0);
+    seen.nodes.into_iter().zip(seen.cost).collect()
+}
+
+/// Like a_star, for searching the positions of a grid `width` wide and
+/// `height` tall. The search's working arrays have a slot for every
+/// position instead of growing as positions are reached, which is much
+/// faster and smaller when most of the grid gets searched. Every position
+/// must be within the grid, and costs must stay below u32::MAX.
+pub fn grid_a_star<I>(
+    width: usize,
+    height: usize,
+    starts: impl IntoIterator<Item = (usize, usize)>,
+    is_goal: impl FnMut(&(usize, usize)) -> bool,
+    neighbors: impl FnMut(&(usize, usize)) -> I,
+    heuristic: impl FnMut(&(usize, usize)) -> usize,
+) -> Option<Path<(usize, usize)>>
+where
+    I: IntoIterator<Item = ((usize, usize), usize)>,
+{
+    let seen = GridSeen::new(width, height);
+    let (seen, goal) = search(seen, starts, is_goal, neighbors, heuristic);
+    goal.map(|i| seen.path_to(i))
+}
+
+/// Like distances, for the positions of a grid `width` wide and `height`
+/// tall. Returns the costs a row at a time from the top, with usize::MAX for
+/// positions that can't be reached. Every position must be within the grid,
+/// and costs must stay below u32::MAX.
+pub fn grid_distances<I>(
+    width: usize,
+    height: usize,
+    starts: impl IntoIterator<Item = (usize, usize)>,
+    neighbors: impl FnMut(&(usize, usize)) -> I,
+) -> Vec<usize>
+where
+    I: IntoIterator<Item = ((usize, usize), usize)>,
+{
+    let seen = GridSeen::new(width, height);
+    let (seen, _) = search(seen, starts, |_| false, neighbors, |_| 0);
+    (0..seen.cost.len()).map(|i| seen.cost(i)).collect()
+}
+
+/// Finds up to `k` cheapest paths from `start` to `goal` that don't visit
+/// any node twice, cheapest first, using Yen's algorithm. Paths of equal
+/// cost come in no particular order.
+pub fn k_cheapest_paths<N, I>(
+    start: N,
+    goal: N,
+    k: usize,
+    neighbors: impl Fn(&N) -> I,
+) -> Vec<Path<N>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    let mut found = Vec::<Path<N>>::new();
+    if k == 0 {
+        return found;
+    }
+    match dijkstra([start], |node| *node == goal, &neighbors) {
+        Some(path) => found.push(path),
+        None => return found,
+    }
+    let mut candidates = Vec::<Path<N>>::new();
+
+    while found.len() < k {
+        let last = &found[found.len() - 1].nodes;
+        // Each candidate follows the last path found up to some spur node,
+        // then leaves it by a move none of the paths found so far make
+        // from there, and never returns to the nodes before the spur.
+        let mut root_cost = 0;
+        for j in 0..last.len() - 1 {
+            let root = &last[..=j];
+            let spur = &last[j];
+            let blocked_nodes: HashSet<&N> = last[..j].iter().collect();
+            let blocked_moves: HashSet<&N> = found
+                .iter()
+                .filter(|path| path.nodes.len() > j + 1 && path.nodes[..=j] == *root)
+                .map(|path| &path.nodes[j + 1])
+                .collect();
+            let (blocked_nodes, blocked_moves) = (&blocked_nodes, &blocked_moves);
+            let spur_path = dijkstra(
+                [spur.clone()],
+                |node| *node == goal,
+                |node: &N| {
+                    let at_spur = node == spur;
+                    neighbors(node).into_iter().filter(move |(next, _)| {
+                        !(blocked_nodes.contains(next) || at_spur && blocked_moves.contains(next))
+                    })
+                },
+            );
+            if let Some(spur_path) = spur_path {
+                let mut nodes = root[..j].to_vec();
+                nodes.extend(spur_path.nodes);
+                let candidate = Path {
+                    nodes,
+                    cost: root_cost + spur_path.cost,
+                };
+                if !candidates.contains(&candidate) && !found.contains(&candidate) {
+                    candidates.push(candidate);
+                }
+            }
+            root_cost += move_cost(&neighbors, spur, &last[j + 1]);
+        }
+
+        let Some(cheapest) = (0..candidates.len()).min_by_key(|&i| candidates[i].cost) else {
+            break;
+        };
+        found.push(candidates.swap_remove(cheapest));
+    }
+    found
+}
+
+/// Returns the cost of moving from `from` to its neighbor `to`, taking the
+/// cheapest if there's more than one way.
+fn move_cost<N, I>(neighbors: impl Fn(&N) -> I, from: &N, to: &N) -> usize
+where
+    N: Eq,
+    I: IntoIterator<Item = (N, usize)>,
+{
+    neighbors(from)
+        .into_iter()
+        .filter(|(next, _)| next == to)
+        .map(|(_, cost)| cost)
+        .min()
+        .expect("path moves between neighbors")
+}
+
+/// Searches from `starts` with A* until a node for which `is_goal` returns
+/// true is reached, or every reachable node has been. Returns everything
+/// reached along with the number of the goal, if one was.
+fn search<N, I, V>(
+    mut seen: V,
+    starts: impl IntoIterator<Item = N>,
+    mut is_goal: impl FnMut(&N) -> bool,
+    mut neighbors: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> usize,
+) -> (V, Option<usize>)
+where
+    I: IntoIterator<Item = (N, usize)>,
+    V: Visited<N>,
+{
+    // Entries are (estimated total cost, cost so far, node index), cheapest
+    // estimate first. Nodes are pushed again when a cheaper way to them is
+    // found, and stale entries skipped when popped.
+    let mut heap = BinaryHeap::<Reverse<(usize, usize, usize)>>::new();
+    for start in starts {
+        let estimate = heuristic(&start);
+        let i = seen.index(start);
+        if seen.cost(i) > 0 {
+            seen.reach(i, 0, None);
+            heap.push(Reverse((estimate, 0, i)));
+        }
+    }
+
+    while let Some(Reverse((_, cost, i))) = heap.pop() {
+        if cost > seen.cost(i) {
+            continue;
+        }
+        let node = seen.node(i);
+        if is_goal(&node) {
+            return (seen, Some(i));
+        }
+        for (next, step_cost) in neighbors(&node) {
+            let next_cost = cost + step_cost;
+            let estimate = next_cost + heuristic(&next);
+            let j = seen.index(next);
+            if next_cost < seen.cost(j) {
+                seen.reach(j, next_cost, Some(i));
+                heap.push(Reverse((estimate, next_cost, j)));
+            }
+        }
+    }
+    (seen, None)
+}
+
+/// Returns an A* heuristic for grid positions: the Manhattan distance to the
+/// nearest of `goals`. It's never too high as long as every move is to an
+/// orthogonal neighbor and costs at least 1.
+pub fn manhattan_heuristic(goals: &[(usize, usize)]) -> impl Fn(&(usize, usize)) -> usize + '_ {
+    move |&(x, y)| {
+        goals
+            .iter()
+            .map(|&(gx, gy)| x.abs_diff(gx) + y.abs_diff(gy))
+            .min()
+            .unwrap_or(0)
+    }
+}
+
+/// The nodes a search has reached, each with the cheapest known cost of
+/// reaching it and the node it was reached from. Nodes are referred to by
+/// number. Nodes that haven't been reached cost usize::MAX.
+trait Visited<N> {
+    /// Returns the number of `node`.
+    fn index(&mut self, node: N) -> usize;
+
+    /// Returns the node numbered `i`.
+    fn node(&self, i: usize) -> N;
+
+    fn cost(&self, i: usize) -> usize;
+
+    fn prev(&self, i: usize) -> Option<usize>;
+
+    /// Records that node `i` can be reached for `cost`, from node `prev`.
+    fn reach(&mut self, i: usize, cost: usize, prev: Option<usize>);
+
+    fn path_to(&self, goal: usize) -> Path<N> {
+        let mut nodes = vec![self.node(goal)];
+        let mut i = goal;
+        while let Some(prev) = self.prev(i) {
+            nodes.push(self.node(prev));
+            i = prev;
+        }
+        nodes.reverse();
+        Path {
+            nodes,
+            cost: self.cost(goal),
+        }
+    }
+}
+
+/// Reached nodes of any hashable type, numbered in the order they were
+/// reached.
+struct Seen<N> {
+    nodes: Vec<N>,
+    indexes: HashMap<N, usize>,
+    cost: Vec<usize>,
+    prev: Vec<Option<usize>>,
+}
+
+impl<N: Clone + Eq + Hash> Seen<N> {
+    fn new() -> Seen<N> {
+        Seen {
+            nodes: Vec::new(),
+            indexes: HashMap::new(),
+            cost: Vec::new(),
+            prev: Vec::new(),
+        }
+    }
+}
+
+impl<N: Clone + Eq + Hash> Visited<N> for Seen<N> {
+    /// Returns the number of `node`, numbering it if it's new.
+    fn index(&mut self, node: N) -> usize {
+        match self.indexes.entry(node) {
+            Entry::Occupied(entry) => *entry.get(),
+            Entry::Vacant(entry) => {
+                let i = self.nodes.len();
+                self.nodes.push(entry.key().clone());
+                self.cost.push(usize::MAX);
+                self.prev.push(None);
+                entry.insert(i);
+                i
+            }
+        }
+    }
+
+    fn node(&self, i: usize) -> N {
+        self.nodes[i].clone()
+    }
+
+    fn cost(&self, i: usize) -> usize {
+        self.cost[i]
+    }
+
+    fn prev(&self, i: usize) -> Option<usize> {
+        self.prev[i]
+    }
+
+    fn reach(&mut self, i: usize, cost: usize, prev: Option<usize>) {
+        self.cost[i] = cost;
+        self.prev[i] = prev;
+    }
+}
+
+/// Reached positions of a grid, numbered a row at a time from the top.
+/// Costs and previous positions are kept as u32s to save memory on large
+/// grids, which limits grids to about four billion positions, and costs to
+/// about as much.
+struct GridSeen {
+    width: usize,
+    cost: Vec<u32>,
+    prev: Vec<u32>,
+}
+
+impl GridSeen {
+    /// Marks a position that wasn't reached from anywhere.
+    const NO_PREV: u32 = u32::MAX;
+
+    /// The cost of a position that hasn't been reached.
+    const UNREACHED: u32 = u32::MAX;
+
+    fn new(width: usize, height: usize) -> GridSeen {
+        let len = width
+            .checked_mul(height)
+            .filter(|&len| len < Self::NO_PREV as usize)
+            .expect("grid too large to search");
+        GridSeen {
+            width,
+            cost: vec![Self::UNREACHED; len],
+            prev: vec![Self::NO_PREV; len],
+        }
+    }
+}
+
+impl Visited<(usize, usize)> for GridSeen {
+    fn index(&mut self, (x, y): (usize, usize)) -> usize {
+        debug_assert!(x < self.width);
+        y * self.width + x
+    }
+
+    fn node(&self, i: usize) -> (usize, usize) {
+        (i % self.width, i / self.width)
+    }
+
+    fn cost(&self, i: usize) -> usize {
+        match self.cost[i] {
+            Self::UNREACHED => usize::MAX,
+            cost => cost as usize,
+        }
+    }
+
+    fn prev(&self, i: usize) -> Option<usize> {
+        match self.prev[i] {
+            Self::NO_PREV => None,
+            prev => Some(prev as usize),
+        }
+    }
+
+    fn reach(&mut self, i: usize, cost: usize, prev: Option<usize>) {
+        self.cost[i] = u32::try_from(cost)
+            .ok()
+            .filter(|&cost| cost != Self::UNREACHED)
+            .expect("cost too large for a grid search");
+        self.prev[i] = prev.map_or(Self::NO_PREV, |prev| prev as u32);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns a neighbors function for a directed graph with the given
+    /// moves, each (from, to, cost).
+    fn graph(moves: &[(char, char, usize)]) -> impl Fn(&char) -> Vec<(char, usize)> + '_ {
+        move |&node| {
+            moves
+                .iter()
+                .filter(|&&(from, _, _)| from == node)
+                .map(|&(_, to, cost)| (to, cost))
+                .collect()
+        }
+    }
+
+    const MOVES: [(char, char, usize); 7] = [
+        ('a', 'b', 1),
+        ('b', 'c', 1),
+        ('a', 'c', 5),
+        ('c', 'd', 1),
+        ('b', 'd', 4),
+        ('x', 'd', 1),
+        ('d', 'e', 2),
+    ];
+
+    fn path(nodes: &str, cost: usize) -> Option<Path<char>> {
+        Some(Path {
+            nodes: nodes.chars().collect(),
+            cost,
+        })
+    }
+
+    #[test]
+    fn dijkstra_paths() {
+        let neighbors = graph(&MOVES);
+        assert_eq!(dijkstra(['a'], |&n| n == 'd', &neighbors), path("abcd", 3));
+        assert_eq!(dijkstra(['a'], |&n| n == 'a', &neighbors), path("a", 0));
+        // The nearest goal wins.
+        assert_eq!(
+            dijkstra(['a'], |&n| "de".contains(n), &neighbors),
+            path("abcd", 3)
+        );
+        // So does the nearest start.
+        assert_eq!(
+            dijkstra(['a', 'x'], |&n| n == 'e', &neighbors),
+            path("xde", 3)
+        );
+        assert_eq!(dijkstra(['d'], |&n| n == 'a', &neighbors), None);
+        assert_eq!(dijkstra([], |&n| n == 'a', &neighbors), None);
+    }
+
+    #[test]
+    fn distances_from_starts() {
+        let neighbors = graph(&MOVES);
+        let mut found: Vec<(char, usize)> = distances(['b'], &neighbors).into_iter().collect();
+        found.sort();
+        assert_eq!(found, [('b', 0), ('c', 1), ('d', 2), ('e', 4)]);
+        let found = distances(['a', 'x'], &neighbors);
+        assert_eq!(
+            (found[&'a'], found[&'x'], found[&'d'], found.len()),
+            (0, 0, 1, 6)
+        );
+    }
+
+    /// Risks for a small grid, where the cheapest way round isn't straight.
+    const RISKS: [[usize; 5]; 4] = [
+        [1, 9, 1, 1, 1],
+        [1, 9, 1, 9, 1],
+        [1, 1, 1, 9, 1],
+        [9, 9, 9, 9, 1],
+    ];
+
+    fn grid_moves(&(x, y): &(usize, usize)) -> Vec<((usize, usize), usize)> {
+        let mut moves = Vec::new();
+        for (dx, dy) in [(0, -1), (-1, 0), (0, 1), (1, 0)] {
+            if let (Some(nx), Some(ny)) = (x.checked_add_signed(dx), y.checked_add_signed(dy)) {
+                if ny < RISKS.len() && nx < RISKS[0].len() {
+                    moves.push(((nx, ny), RISKS[ny][nx]));
+                }
+            }
+        }
+        moves
+    }
+
+    #[test]
+    fn a_star_agrees_with_dijkstra() {
+        let goal = (4, 3);
+        let goals = [goal];
+        let heuristic = manhattan_heuristic(&goals);
+        let expected = dijkstra([(0, 0)], |&n| n == goal, grid_moves).unwrap();
+        let found = a_star([(0, 0)], |&n| n == goal, grid_moves, &heuristic).unwrap();
+        let winding = [
+            (0, 0),
+            (0, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2),
+            (2, 1),
+            (2, 0),
+            (3, 0),
+            (4, 0),
+            (4, 1),
+            (4, 2),
+            (4, 3),
+        ];
+        assert_eq!(expected.nodes, winding);
+        assert_eq!(expected.cost, 11);
+        assert_eq!(found, expected);
+        let on_grid = grid_a_star(5, 4, [(0, 0)], |&n| n == goal, grid_moves, &heuristic);
+        assert_eq!(on_grid, Some(found));
+
+        let all = distances([(0, 0)], grid_moves);
+        let on_grid = grid_distances(5, 4, [(0, 0)], grid_moves);
+        for (i, cost) in on_grid.into_iter().enumerate() {
+            assert_eq!(all[&(i % 5, i / 5)], cost);
+        }
+    }
+
+    #[test]
+    fn manhattan_distance_to_nearest_goal() {
+        let heuristic = manhattan_heuristic(&[(0, 0), (5, 5)]);
+        assert_eq!(heuristic(&(1, 2)), 3);
+        assert_eq!(heuristic(&(4, 6)), 2);
+        assert_eq!(manhattan_heuristic(&[])(&(3, 3)), 0);
+    }
+
+    #[test]
+    fn k_cheapest_paths_in_graph() {
+        let neighbors = graph(&MOVES);
+        let found = k_cheapest_paths('a', 'e', 5, &neighbors);
+        let want = [path("abcde", 5), path("abde", 7), path("acde", 8)];
+        assert_eq!(found.into_iter().map(Some).collect::<Vec<_>>(), want);
+        assert_eq!(
+            k_cheapest_paths('a', 'e', 1, &neighbors),
+            [path("abcde", 5).unwrap()]
+        );
+        assert_eq!(k_cheapest_paths('a', 'e', 0, &neighbors), []);
+        assert_eq!(
+            k_cheapest_paths('a', 'a', 3, &neighbors),
+            [path("a", 0).unwrap()]
+        );
+        assert_eq!(k_cheapest_paths('e', 'a', 3, &neighbors), []);
+    }
+}
@@ -0,0 +1,375 @@
+//! Property tests checking that puzzles solved two ways agree, on random
+//! inputs. The crate has no dependencies, so random inputs are made with a
+//! small seeded generator, and failing inputs are shrunk by hand-written
+//! rules for making each kind of input smaller.
+
+use std::fmt::Debug;
+use std::ops::RangeInclusive;
+use std::panic;
+use std::panic::AssertUnwindSafe;
+
+use crate::day06::Lanternfish;
+use crate::day07::Day7;
+use crate::day14::Polymer;
+use crate::Answer;
+use crate::AnswerValue;
+use crate::Day;
+use crate::PuzzleError;
+
+/// How many random inputs each property is checked against.
+const CASES: u64 = 200;
+
+/// A pseudo-random number generator (SplitMix64). The same seed always gives
+/// the same numbers, so a failing case can be found again from its seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `range`. Numbers near the start are very slightly
+    /// more likely, which doesn't matter for testing.
+    fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = range.into_inner();
+        start + (self.next_u64() % (end - start + 1) as u64) as usize
+    }
+
+    /// Returns a vector with a length in `len`, of items made by `item`.
+    fn vec<T>(
+        &mut self,
+        len: RangeInclusive<usize>,
+        mut item: impl FnMut(&mut Rng) -> T,
+    ) -> Vec<T> {
+        let len = self.range(len);
+        (0..len).map(|_| item(self)).collect()
+    }
+}
+
+/// Checks `property` against CASES inputs made by `generate`. If it fails,
+/// panics with the smallest failing input `shrink` leads to.
+fn check<T: Clone + Debug>(
+    generate: impl Fn(&mut Rng) -> T,
+    shrink: impl Fn(&T) -> Vec<T>,
+    property: impl Fn(&T) -> Result<(), String>,
+) {
+    for seed in 0..CASES {
+        let input = generate(&mut Rng::new(seed));
+        if let Err(msg) = run(&property, &input) {
+            let (input, msg) = minimize(input, msg, &shrink, &property);
+            panic!(
+                "property failed (seed {}): {}\nsmallest failing input: {:?}",
+                seed, msg, input
+            );
+        }
+    }
+}
+
+/// Repeatedly replaces a failing input with the first of the smaller inputs
+/// from `shrink` that also fails, until none of them do. Returns the last
+/// failing input along with how it failed.
+fn minimize<T>(
+    mut input: T,
+    mut msg: String,
+    shrink: impl Fn(&T) -> Vec<T>,
+    property: impl Fn(&T) -> Result<(), String>,
+) -> (T, String) {
+    'shrink: loop {
+        for smaller in shrink(&input) {
+            if let Err(smaller_msg) = run(&property, &smaller) {
+                input = smaller;
+                msg = smaller_msg;
+                continue 'shrink;
+            }
+        }
+        return (input, msg);
+    }
+}
+
+/// Checks `property` against `input`, counting a panic as a failure.
+fn run<T>(property: impl Fn(&T) -> Result<(), String>, input: &T) -> Result<(), String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| property(input))) {
+        Ok(result) => result,
+        Err(payload) => Err(match payload.downcast_ref::<&str>() {
+            Some(msg) => format!("panicked: {}", msg),
+            None => match payload.downcast_ref::<String>() {
+                Some(msg) => format!("panicked: {}", msg),
+                None => String::from("panicked"),
+            },
+        }),
+    }
+}
+
+/// Returns copies of `items` with one item removed, then with one item
+/// replaced by each of the smaller items from `shrink_item`.
+fn shrink_vec<T: Clone>(items: &[T], shrink_item: impl Fn(&T) -> Vec<T>) -> Vec<Vec<T>> {
+    let mut smaller = Vec::new();
+    for i in 0..items.len() {
+        let mut v = items.to_vec();
+        v.remove(i);
+        smaller.push(v);
+    }
+    for (i, item) in items.iter().enumerate() {
+        for smaller_item in shrink_item(item) {
+            let mut v = items.to_vec();
+            v[i] = smaller_item;
+            smaller.push(v);
+        }
+    }
+    smaller
+}
+
+/// Returns smaller numbers to try in place of `n`, biggest jumps first.
+fn shrink_number(n: usize) -> Vec<usize> {
+    let mut smaller = vec![0, n / 2, n.saturating_sub(1)];
+    smaller.dedup();
+    smaller.retain(|&m| m < n);
+    smaller
+}
+
+fn number(result: Result<Answer, PuzzleError>) -> Result<i64, String> {
+    match result {
+        Ok(Answer {
+            value: AnswerValue::Number(n),
+            ..
+        }) => Ok(n),
+        Ok(answer) => Err(format!("expected a number, got {:?}", answer.value)),
+        Err(err) => Err(format!("{:?}", err)),
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PolymerCase {
+    template: Vec<u8>,
+    rules: Vec<(u8, u8, u8)>,
+    steps: usize,
+}
+
+impl PolymerCase {
+    /// Elements are drawn from only a few letters, so that most pairs have
+    /// a rule.
+    const ELEMENTS: RangeInclusive<usize> = 0..=3;
+
+    fn generate(rng: &mut Rng) -> PolymerCase {
+        let element = |rng: &mut Rng| b'A' + rng.range(Self::ELEMENTS) as u8;
+        PolymerCase {
+            template: rng.vec(1..=8, element),
+            rules: rng.vec(0..=16, |rng| (element(rng), element(rng), element(rng))),
+            steps: rng.range(0..=8),
+        }
+    }
+
+    fn shrink(&self) -> Vec<PolymerCase> {
+        let mut smaller = Vec::new();
+        let shrink_element = |&e: &u8| match e {
+            b'A' => vec![],
+            _ => vec![b'A'],
+        };
+        for template in shrink_vec(&self.template, shrink_element) {
+            if !template.is_empty() {
+                smaller.push(PolymerCase {
+                    template,
+                    ..self.clone()
+                });
+            }
+        }
+        for rules in shrink_vec(&self.rules, |_| vec![]) {
+            smaller.push(PolymerCase {
+                rules,
+                ..self.clone()
+            });
+        }
+        for steps in shrink_number(self.steps) {
+            smaller.push(PolymerCase {
+                steps,
+                ..self.clone()
+            });
+        }
+        smaller
+    }
+
+    fn polymer(&self) -> Polymer {
+        let mut s: String = self.template.iter().map(|&e| e as char).collect();
+        s += "\n\n";
+        for &(l, r, b) in &self.rules {
+            s += &format!("{}{} -> {}\n", l as char, r as char, b as char);
+        }
+        s.parse().expect("generated polymer should parse")
+    }
+}
+
+#[test]
+fn polymer_step_agrees_with_hist() {
+    check(PolymerCase::generate, PolymerCase::shrink, |case| {
+        let mut polymer = case.polymer();
+        let hist = polymer.hist(case.steps);
+        polymer.step(case.steps);
+        // With no steps, hist just counts the elements.
+        let stepped = polymer.hist(0);
+        match stepped == hist {
+            true => Ok(()),
+            false => Err(format!("step counts {:?}, hist counts {:?}", stepped, hist)),
+        }
+    });
+}
+
+#[derive(Clone, Debug)]
+struct LanternfishCase {
+    timers: Vec<usize>,
+    days: usize,
+}
+
+impl LanternfishCase {
+    fn generate(rng: &mut Rng) -> LanternfishCase {
+        LanternfishCase {
+            timers: rng.vec(1..=10, |rng| rng.range(0..=8)),
+            days: rng.range(0..=80),
+        }
+    }
+
+    fn shrink(&self) -> Vec<LanternfishCase> {
+        // Lanternfish can't parse an empty list.
+        let mut smaller = Vec::new();
+        for timers in shrink_vec(&self.timers, |&t| shrink_number(t)) {
+            if timers.is_empty() {
+                continue;
+            }
+            smaller.push(LanternfishCase {
+                timers,
+                ..self.clone()
+            });
+        }
+        for days in shrink_number(self.days) {
+            smaller.push(LanternfishCase {
+                days,
+                ..self.clone()
+            });
+        }
+        smaller
+    }
+}
+
+#[test]
+fn lanternfish_advance_agrees_with_each_fish() {
+    check(LanternfishCase::generate, LanternfishCase::shrink, |case| {
+        let timers: Vec<String> = case.timers.iter().map(|t| t.to_string()).collect();
+        let mut lanternfish: Lanternfish =
+            timers.join(",").parse().map_err(|err| format!("{}", err))?;
+        let mut fish = case.timers.clone();
+        for _ in 0..case.days {
+            lanternfish.advance();
+            let mut spawned = 0;
+            for t in fish.iter_mut() {
+                match *t {
+                    0 => {
+                        *t = 6;
+                        spawned += 1;
+                    }
+                    _ => *t -= 1,
+                }
+            }
+            fish.extend(std::iter::repeat_n(8, spawned));
+        }
+        // Lanternfish display as the number of fish with each timer.
+        let mut counted = [0; 9];
+        for &t in &fish {
+            counted[t] += 1;
+        }
+        let counted: Vec<String> = counted.iter().map(|n| n.to_string()).collect();
+        let (counted, advanced) = (counted.join(","), lanternfish.to_string());
+        match counted == advanced {
+            true => Ok(()),
+            false => Err(format!(
+                "each fish gives {}, advance gives {}",
+                counted, advanced
+            )),
+        }
+    });
+}
+
+fn generate_crabs(rng: &mut Rng) -> Vec<usize> {
+    rng.vec(1..=20, |rng| rng.range(0..=100))
+}
+
+fn shrink_crabs(positions: &[usize]) -> Vec<Vec<usize>> {
+    let mut smaller = shrink_vec(positions, |&p| shrink_number(p));
+    smaller.retain(|positions| !positions.is_empty());
+    smaller
+}
+
+fn crab_positions(positions: &[usize]) -> Vec<i64> {
+    positions.iter().map(|&p| p as i64).collect()
+}
+
+#[test]
+fn crab_scan_agrees_with_median() {
+    check(
+        generate_crabs,
+        |p| shrink_crabs(p),
+        |positions| {
+            let mut positions = crab_positions(positions);
+            let scanned = number(Day7::part1(positions.clone()))?;
+            positions.sort_unstable();
+            let median = positions[positions.len() / 2];
+            let closed_form = positions.iter().map(|p| (p - median).abs()).sum();
+            match scanned == closed_form {
+                true => Ok(()),
+                false => Err(format!(
+                    "scan gives {}, median {} gives {}",
+                    scanned, median, closed_form
+                )),
+            }
+        },
+    );
+}
+
+#[test]
+fn crab_scan_agrees_with_mean() {
+    check(
+        generate_crabs,
+        |p| shrink_crabs(p),
+        |positions| {
+            let positions = crab_positions(positions);
+            let scanned = number(Day7::part2(positions.clone()))?;
+            // The cheapest position is within a half of the mean, so it's the
+            // mean rounded one way or the other.
+            let sum: i64 = positions.iter().sum();
+            let len = positions.len() as i64;
+            let fuel = |pos: i64| -> i64 {
+                positions
+                    .iter()
+                    .map(|p| (p - pos).abs() * ((p - pos).abs() + 1) / 2)
+                    .sum()
+            };
+            let closed_form = fuel(sum / len).min(fuel((sum + len - 1) / len));
+            match scanned == closed_form {
+                true => Ok(()),
+                false => Err(format!(
+                    "scan gives {}, mean {}/{} gives {}",
+                    scanned, sum, len, closed_form
+                )),
+            }
+        },
+    );
+}
+
+#[test]
+fn failing_inputs_are_shrunk() {
+    let property = |v: &Vec<usize>| match v.iter().any(|&n| n >= 10) {
+        true => Err(String::from("found 10 or more")),
+        false => Ok(()),
+    };
+    let shrink = |v: &Vec<usize>| shrink_vec(v, |&n| shrink_number(n));
+    let input = Rng::new(0).vec(20..=20, |rng| rng.range(0..=1000));
+    let msg = property(&input).unwrap_err();
+    let (input, _) = minimize(input, msg, shrink, property);
+    assert_eq!(input, vec![10]);
+}
@@ -0,0 +1,395 @@
+//! What every puzzle provides, and what solving one produces.
+
+use std::error::Error;
+use std::fmt;
+use std::fmt::Display;
+use std::fmt::Formatter;
+use std::io;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::time::Duration;
+use std::time::Instant;
+
+use crate::grid::Grid;
+use crate::parse::ParseError;
+
+/// One part of one day's puzzle.
+pub trait Puzzle {
+    fn day(&self) -> u32;
+    fn part(&self) -> u32;
+    fn title(&self) -> &'static str;
+
+    /// Parses the input and solves the puzzle.
+    fn solve(&self, input: &str) -> Result<Answer, PuzzleError> {
+        self.solve_timed(input).map(|(answer, _)| answer)
+    }
+
+    /// Parses the input and solves the puzzle, recording how long each step
+    /// took.
+    fn solve_timed(&self, input: &str) -> Result<(Answer, Timing), PuzzleError>;
+
+    /// Parses the input and draws a picture of the solution, or returns None
+    /// if the puzzle doesn't draw one.
+    fn render(
+        &self,
+        input: &str,
+        options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError>;
+
+    /// Returns the puzzle's name on the command line, like "15_2".
+    fn id(&self) -> String {
+        format!("{}_{}", self.day(), self.part())
+    }
+}
+
+/// Both parts of one day's puzzle. Both parts read the same input, so it's
+/// parsed once by the day, then handed to whichever part is being solved.
+pub trait Day {
+    const DAY: u32;
+    const TITLE: &'static str;
+    type Input;
+
+    fn parse(input: &str) -> Result<Self::Input, PuzzleError>;
+    fn part1(input: Self::Input) -> Result<Answer, PuzzleError>;
+    fn part2(input: Self::Input) -> Result<Answer, PuzzleError>;
+
+    /// Draws a picture of one part's solution. Most days don't.
+    fn render(
+        _part: u32,
+        _input: &Self::Input,
+        _options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError> {
+        Ok(None)
+    }
+}
+
+/// Adapts one part of a Day to the Puzzle trait.
+struct DayPart<D: Day> {
+    part: u32,
+    day: PhantomData<D>,
+}
+
+impl<D: Day> Puzzle for DayPart<D> {
+    fn day(&self) -> u32 {
+        D::DAY
+    }
+
+    fn part(&self) -> u32 {
+        self.part
+    }
+
+    fn title(&self) -> &'static str {
+        D::TITLE
+    }
+
+    fn solve_timed(&self, input: &str) -> Result<(Answer, Timing), PuzzleError> {
+        let begin = Instant::now();
+        let input = D::parse(input).map_err(|err| err.in_puzzle(self.id()))?;
+        let parsed = Instant::now();
+        let answer = match self.part {
+            1 => D::part1(input),
+            _ => D::part2(input),
+        }
+        .map_err(|err| err.in_puzzle(self.id()))?;
+        let timing = Timing {
+            parse: parsed - begin,
+            solve: parsed.elapsed(),
+        };
+        Ok((answer, timing))
+    }
+
+    fn render(
+        &self,
+        input: &str,
+        options: &RenderOptions,
+    ) -> Result<Option<Rendering>, PuzzleError> {
+        let input = D::parse(input).map_err(|err| err.in_puzzle(self.id()))?;
+        D::render(self.part, &input, options).map_err(|err| err.in_puzzle(self.id()))
+    }
+}
+
+/// How long it took to parse a puzzle's input and to solve it.
+#[derive(Clone, Copy)]
+pub struct Timing {
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+/// A picture of a puzzle's solution.
+pub struct Rendering {
+    /// The picture drawn with characters, one row per line.
+    pub text: String,
+
+    /// The picture as an image, one RGB pixel per grid cell.
+    pub image: Grid<[u8; 3]>,
+
+    /// The picture as an SVG document, for puzzles whose pictures are lines
+    /// rather than grids of cells.
+    pub svg: Option<String>,
+}
+
+/// How a picture should be drawn.
+#[derive(Clone, Copy, Debug)]
+pub struct RenderOptions {
+    /// How many characters wide the text can be.
+    pub width: usize,
+
+    /// Whether to draw the other part's solution over this part's, for
+    /// puzzles where the two can be compared.
+    pub overlay: bool,
+
+    /// Whether the text can be highlighted with terminal escape codes.
+    pub color: bool,
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions {
+            width: 80,
+            overlay: false,
+            color: false,
+        }
+    }
+}
+
+impl Rendering {
+    /// Writes the image in binary PPM format.
+    pub fn write_ppm(&self, w: &mut impl Write) -> io::Result<()> {
+        write!(
+            w,
+            "P6\n{} {}\n255\n",
+            self.image.width(),
+            self.image.height()
+        )?;
+        for pixel in self.image.iter() {
+            w.write_all(pixel)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    puzzles: Vec<Box<dyn Puzzle>>,
+}
+
+impl Registry {
+    pub fn new() -> Registry {
+        Registry {
+            puzzles: Vec::<Box<dyn Puzzle>>::new(),
+        }
+    }
+
+    /// Registers both parts of a day's puzzle.
+    pub fn register<D: Day + 'static>(&mut self) {
+        for part in 1..=2 {
+            self.puzzles.push(Box::new(DayPart::<D> {
+                part,
+                day: PhantomData,
+            }));
+        }
+    }
+
+    /// Returns an iterator over registered puzzles in the order they were
+    /// registered.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Puzzle> {
+        self.puzzles.iter().map(|p| p.as_ref())
+    }
+
+    /// Returns the puzzles matching a selector from the command line: "all"
+    /// for every puzzle, a day like "7" for both parts of that day, or an id
+    /// like "15_2" for a single puzzle.
+    pub fn select(&self, selector: &str) -> Vec<&dyn Puzzle> {
+        self.iter()
+            .filter(|p| selector == "all" || selector == p.day().to_string() || selector == p.id())
+            .collect()
+    }
+}
+
+/// A puzzle's answer. The value is what gets submitted to the puzzle site.
+/// Fields are named intermediate values that are useful for checking work.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Answer {
+    pub value: AnswerValue,
+    pub fields: Vec<(&'static str, i64)>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnswerValue {
+    Number(i64),
+
+    /// Multi-line text, for puzzles whose answer is drawn as ASCII art.
+    Text(String),
+}
+
+impl Answer {
+    pub fn number(n: i64) -> Answer {
+        Answer {
+            value: AnswerValue::Number(n),
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn text(s: String) -> Answer {
+        let s = String::from(s.trim_end_matches('\n'));
+        Answer {
+            value: AnswerValue::Text(s),
+            fields: Vec::new(),
+        }
+    }
+
+    /// Adds a named auxiliary value to the answer.
+    pub fn with(mut self, name: &'static str, value: i64) -> Answer {
+        self.fields.push((name, value));
+        self
+    }
+}
+
+impl Display for Answer {
+    /// Formats the answer's value on its own line (or lines, for text),
+    /// followed by one "name: value" line for each field.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.value {
+            AnswerValue::Number(n) => write!(f, "{}", n)?,
+            AnswerValue::Text(s) => f.write_str(s)?,
+        }
+        for (name, value) in &self.fields {
+            write!(f, "\n{}: {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// An error solving a puzzle, usually caused by malformed input.
+#[derive(Debug)]
+pub struct PuzzleError {
+    kind: PuzzleErrorKind,
+    message: String,
+
+    /// Id of the puzzle that failed, like "15_2". Filled in by DayPart,
+    /// since parsers and solvers don't know which puzzle they're part of.
+    puzzle: Option<String>,
+
+    /// Line in the input where the problem was found, starting at 1.
+    line: Option<usize>,
+
+    /// Column in the line where the problem was found, starting at 1.
+    column: Option<usize>,
+
+    /// The parse error this error was made from, if any.
+    source: Option<Box<ParseError>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PuzzleErrorKind {
+    /// The input could not be parsed.
+    Parse,
+
+    /// The input parsed, but it breaks an assumption the solver relies on.
+    Invalid,
+
+    /// The input is valid, but has no answer.
+    NoSolution,
+}
+
+impl PuzzleError {
+    pub fn new(kind: PuzzleErrorKind, message: impl Into<String>) -> PuzzleError {
+        PuzzleError {
+            kind,
+            message: message.into(),
+            puzzle: None,
+            line: None,
+            column: None,
+            source: None,
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> PuzzleError {
+        PuzzleError::new(PuzzleErrorKind::Invalid, message)
+    }
+
+    pub fn no_solution(message: impl Into<String>) -> PuzzleError {
+        PuzzleError::new(PuzzleErrorKind::NoSolution, message)
+    }
+
+    /// Sets the line where the problem was found, counting from 1.
+    pub fn at_line(mut self, line: usize) -> PuzzleError {
+        self.line = Some(line);
+        self
+    }
+
+    /// Sets the line and column where the problem was found, counting
+    /// from 1.
+    pub fn at(mut self, line: usize, column: usize) -> PuzzleError {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    /// Sets the id of the puzzle that failed, unless it's already set.
+    pub fn in_puzzle(mut self, id: String) -> PuzzleError {
+        self.puzzle.get_or_insert(id);
+        self
+    }
+
+    pub fn kind(&self) -> PuzzleErrorKind {
+        self.kind
+    }
+
+    /// Returns the line where the problem was found, counting from 1.
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    /// Returns the column where the problem was found, counting from 1.
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    /// Returns the offending input line with the problem underlined, if the
+    /// error came from parsing.
+    pub fn snippet(&self) -> Option<String> {
+        self.source.as_ref().map(|err| err.snippet())
+    }
+}
+
+impl Display for PuzzleError {
+    /// Formats the error like "15_2: line 3, column 7: parsing input: ...",
+    /// leaving out whatever isn't known.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if let Some(puzzle) = &self.puzzle {
+            write!(f, "{}: ", puzzle)?;
+        }
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => write!(f, "line {}, column {}: ", line, column)?,
+            (Some(line), None) => write!(f, "line {}: ", line)?,
+            _ => (),
+        }
+        let what = match self.kind {
+            PuzzleErrorKind::Parse => "parsing input",
+            PuzzleErrorKind::Invalid => "invalid input",
+            PuzzleErrorKind::NoSolution => "no solution",
+        };
+        write!(f, "{}: {}", what, self.message)
+    }
+}
+
+impl Error for PuzzleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|err| err as &(dyn Error + 'static))
+    }
+}
+
+impl From<ParseError> for PuzzleError {
+    fn from(err: ParseError) -> Self {
+        let mut puzzle_err = PuzzleError::new(
+            PuzzleErrorKind::Parse,
+            format!("expected {}, found {}", err.expected, err.found),
+        )
+        .at(err.line, err.column);
+        puzzle_err.source = Some(Box::new(err));
+        puzzle_err
+    }
+}